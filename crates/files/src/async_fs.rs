@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+use crate::{Error, MAX_SYMLINKS, preserve_owner, temp_sibling};
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    runtime.block_on(async {
+        let dir = crate::TempDir::new().unwrap();
+        let path = dir.join("config.toml");
+        let path = path.to_str().unwrap();
+
+        let value = crate::Toml::get_async::<toml::Table>(path).await.unwrap();
+        assert!(value.is_empty());
+        crate::Toml::save_async(path, &toml::toml! { value = 1 }).await.unwrap();
+        assert_eq!(crate::Toml::load_async::<toml::Table>(path).await.unwrap()["value"].as_integer(), Some(1));
+        tokio::fs::remove_file(path).await.unwrap();
+        assert!(matches!(crate::Json::save_async(path, &1).await, Err(Error::IO(_))));
+
+        // Sensitive files are checked without blocking
+        #[cfg(unix)]
+        {
+            crate::Toml::create_with_mode_async(path, &toml::Table::new(), 0o644).await.unwrap();
+            assert!(matches!(crate::Toml::load_sensitive_async::<toml::Table>(path).await, Err(Error::InsecurePermissions { .. })));
+            tokio::fs::remove_file(path).await.unwrap();
+        }
+
+        // Dispatch and CSV have async versions too
+        let path = path.replace(".toml", ".json");
+        crate::FileFormat::create_async(&path, &vec![1, 2]).await.unwrap();
+        assert_eq!(crate::FileFormat::load_async::<Vec<u32>>(&path).await.unwrap(), vec![1, 2]);
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        let path = path.replace(".json", ".csv");
+        #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+        struct Row { id: u32 }
+        crate::Csv::create_async(&path, &[Row { id: 1 }, Row { id: 2 }]).await.unwrap();
+        assert_eq!(crate::Csv::load_async::<Row>(&path).await.unwrap(), vec![Row { id: 1 }, Row { id: 2 }]);
+        tokio::fs::remove_file(&path).await.unwrap();
+    });
+}
+
+/// Async version of [`check_permissions`](crate::check_permissions) using non-blocking file IO.
+pub async fn check_permissions_async(file_path: &str) -> Result<(), Error> {
+    crate::permissions::check_metadata(file_path, &tokio::fs::metadata(file_path).await?)
+}
+
+/// Async version of [`write_atomic_with_mode`](crate::write_atomic_with_mode) using non-blocking file IO.
+/// Readers will either see the old or the new content, never a partially written file.
+/// Symbolic links are written through, and the permissions and owner (where allowed) of an already existing target are preserved.
+pub async fn write_atomic_async(file_path: impl AsRef<Path>, content: &[u8], mode: Option<u32>) -> Result<(), Error> {
+    let file_path = resolve_symlink(file_path.as_ref()).await?;
+    let file_path = file_path.as_path();
+    let temp_path = temp_sibling(file_path);
+
+    let result = async {
+        // Create the temporary file, never readable by others if a mode is requested
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            options.mode(mode);
+        }
+        let mut file = options.open(&temp_path).await?;
+
+        // Apply the requested permissions or preserve the existing ones
+        match mode {
+            #[cfg(unix)]
+            Some(mode) => file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(mode)).await?,
+            _ => if let Ok(metadata) = tokio::fs::metadata(file_path).await {
+                file.set_permissions(metadata.permissions()).await?;
+                preserve_owner(&file, &metadata);
+            },
+        }
+
+        // Write the content into the temporary file
+        file.write_all(content).await?;
+        file.sync_all().await?;
+
+        // Replace the target with the temporary file and persist the rename
+        tokio::fs::rename(&temp_path, file_path).await?;
+        sync_parent(file_path).await
+    }.await;
+
+    // Clean up the temporary file if anything failed
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+    }
+    Ok(result?)
+}
+
+/// Async version of [`resolve_symlink`](crate::resolve_symlink) using non-blocking file IO.
+async fn resolve_symlink(file_path: &Path) -> std::io::Result<PathBuf> {
+    let mut resolved = file_path.to_path_buf();
+    for _ in 0..MAX_SYMLINKS {
+        match tokio::fs::symlink_metadata(&resolved).await {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let link = tokio::fs::read_link(&resolved).await?;
+                resolved = resolved.parent().map_or_else(|| link.clone(), |parent| parent.join(&link));
+            },
+            _ => return Ok(resolved),
+        }
+    }
+    Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("too many levels of symbolic links in {}", file_path.display())))
+}
+
+/// Async version of [`sync_parent`](crate::sync_parent) using non-blocking file IO.
+async fn sync_parent(file_path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = file_path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        tokio::fs::File::open(parent).await?.sync_all().await?;
+    }
+    #[cfg(not(unix))]
+    let _ = file_path;
+    Ok(())
+}
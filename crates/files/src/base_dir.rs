@@ -0,0 +1,123 @@
+use std::{fs, path::{Component, Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Format, WriteOptions, write_atomic};
+
+#[test]
+fn test() {
+    use crate::Toml;
+
+    let dir = std::env::temp_dir().join(format!("util_files_base_dir_{}", std::process::id()));
+    let base = BaseDir::open(dir.join("data")).unwrap();
+
+    base.create::<Toml, _>("users/../alice.toml", &toml::toml! { name = "Alice" }).unwrap();
+    assert_eq!(base.load::<Toml, toml::Table>("alice.toml").unwrap()["name"].as_str(), Some("Alice"));
+    base.save_with::<Toml, _>("alice.toml", &toml::toml! { tags = ["a"] }, &WriteOptions::new().arrays(crate::ArrayLayout::Inline)).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("data/alice.toml")).unwrap(), "tags = [\"a\"]\n");
+    assert!(matches!(base.resolve("../secret.toml"), Err(Error::PathEscape(_))));
+    assert!(matches!(base.resolve("/etc/passwd"), Err(Error::PathEscape(_))));
+
+    // Symbolic links pointing outside of the base directory are rejected
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&dir, dir.join("data/link")).unwrap();
+        assert!(matches!(base.resolve("link/secret.toml"), Err(Error::PathEscape(_))));
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+// #================#
+// #=== BASE DIR ===#
+
+/// Handle to a base directory, resolving user-supplied relative paths strictly inside of it.
+/// Absolute paths, `..` traversal leaving the directory and symbolic links pointing outside of it are rejected.
+#[derive(Debug, Clone)]
+pub struct BaseDir {
+    root: PathBuf,
+}
+impl BaseDir {
+    /// Opens the base directory, creating it if it does not exist.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, Error> {
+        fs::create_dir_all(root.as_ref())?;
+        Ok(BaseDir { root: fs::canonicalize(root)? })
+    }
+    /// Returns the canonical path of the base directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+    /// Resolves the relative path inside the base directory.
+    /// Fails with [`Error::PathEscape`] if the path would point outside of it.
+    pub fn resolve(&self, relative: impl AsRef<Path>) -> Result<PathBuf, Error> {
+        let relative = relative.as_ref();
+        let escape = || Error::PathEscape(relative.to_string_lossy().into_owned());
+
+        // Normalize the path without touching the file system
+        let mut normalized = PathBuf::new();
+        for component in relative.components() {
+            match component {
+                Component::Normal(part) => normalized.push(part),
+                Component::CurDir => {},
+                Component::ParentDir => if !normalized.pop() {
+                    return Err(escape());
+                },
+                Component::RootDir | Component::Prefix(_) => return Err(escape()),
+            }
+        }
+        let path = self.root.join(&normalized);
+
+        // Resolve symbolic links of the longest existing part of the path
+        let mut existing = path.as_path();
+        while fs::symlink_metadata(existing).is_err() {
+            existing = existing.parent().ok_or_else(escape)?;
+        }
+        let resolved = fs::canonicalize(existing)?;
+        if !resolved.starts_with(&self.root) {
+            return Err(escape());
+        }
+        let missing = path.strip_prefix(existing).map_err(|_| escape())?;
+        Ok(if missing.as_os_str().is_empty() { resolved } else { resolved.join(missing) })
+    }
+    /// Tries to load a file from the relative path. If it doesn't find one, it creates one from default.
+    pub fn get<F: Format, T: for<'de> Deserialize<'de> + Serialize + Default>(&self, relative: impl AsRef<Path>) -> Result<T, Error> {
+        let path = self.resolve(relative)?;
+        if !fs::exists(&path)? {
+            Self::write(&path, &F::to_bytes(&T::default())?)?;
+        }
+        F::from_bytes::<T>(&fs::read(&path)?)
+    }
+    /// Tries to create a new file at the relative path from the struct provided, creating missing directories.
+    pub fn create<F: Format, T: Serialize>(&self, relative: impl AsRef<Path>, content: &T) -> Result<(), Error> {
+        Self::write(&self.resolve(relative)?, &F::to_bytes(content)?)
+    }
+    /// Tries to create a new file at the relative path from the struct provided written with the options, creating missing directories.
+    pub fn create_with<F: Format, T: Serialize>(&self, relative: impl AsRef<Path>, content: &T, options: &WriteOptions) -> Result<(), Error> {
+        Self::write(&self.resolve(relative)?, &F::to_bytes_with(content, options)?)
+    }
+    /// Tries to create a new file at the relative path from struct default, creating missing directories.
+    pub fn create_default<F: Format, T: Default + Serialize>(&self, relative: impl AsRef<Path>) -> Result<(), Error> {
+        self.create::<F, T>(relative, &T::default())
+    }
+    /// Tries to save the struct to an existing file at the relative path.
+    pub fn save<F: Format, T: Serialize>(&self, relative: impl AsRef<Path>, content: &T) -> Result<(), Error> {
+        let path = self.resolve(relative)?;
+        fs::metadata(&path)?;
+        write_atomic(&path, &F::to_bytes(content)?)
+    }
+    /// Tries to save the struct written with the options to an existing file at the relative path.
+    pub fn save_with<F: Format, T: Serialize>(&self, relative: impl AsRef<Path>, content: &T, options: &WriteOptions) -> Result<(), Error> {
+        let path = self.resolve(relative)?;
+        fs::metadata(&path)?;
+        write_atomic(&path, &F::to_bytes_with(content, options)?)
+    }
+    /// Tries to load a file at the relative path into the required struct.
+    pub fn load<F: Format, T: for<'de> Deserialize<'de>>(&self, relative: impl AsRef<Path>) -> Result<T, Error> {
+        F::from_bytes::<T>(&fs::read(self.resolve(relative)?)?)
+    }
+    /// Writes the content to the resolved path, creating missing directories.
+    fn write(path: &Path, content: &[u8]) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(path, content)
+    }
+}
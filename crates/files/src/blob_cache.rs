@@ -0,0 +1,178 @@
+use std::{fs, path::{Path, PathBuf}, time::SystemTime};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Error, Format, LockFile, manifest::to_hex, walk_files, write_atomic};
+
+#[test]
+fn test() {
+    let dir = crate::TempDir::new().unwrap();
+    let cache = BlobCache::open(dir.path(), 10).unwrap();
+
+    let a = cache.put(b"aaaa").unwrap();
+    assert_eq!(a, "61be55a8e2f6b4e172338bddf184d6dbee29c98853e0a0485ecee7f27b9af0b4");
+    assert_eq!(cache.get(&a).unwrap().unwrap(), b"aaaa");
+    let b = cache.put_value::<crate::Json, _>(&"bb").unwrap();
+    assert_eq!(cache.get_value::<crate::Json, String>(&b).unwrap().unwrap(), "bb");
+    assert_eq!(cache.size().unwrap(), 8);
+
+    // Reading `a` makes `b` the least recently used blob, which is evicted first
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    cache.get(&a).unwrap();
+    let c = cache.put(b"cccc").unwrap();
+    assert!(cache.contains(&a) && cache.contains(&c) && !cache.contains(&b));
+    assert_eq!(cache.get(&b).unwrap(), None);
+    assert!(matches!(cache.get("../secret"), Err(Error::InvalidKey(_))));
+}
+
+// #==================#
+// #=== BLOB CACHE ===#
+
+/// Directory storing blobs by the SHA-256 of their content, keeping its total size under a limit.
+///
+/// Blobs are written atomically, so processes sharing the directory never see partial blobs and
+/// concurrent writes of the same content are harmless. Eviction removes the least recently used
+/// blobs and is guarded by a [`LockFile`], a process finding it held skips eviction.
+/// Access times are tracked in the modification time of the blob, as `atime` is often disabled.
+#[derive(Debug, Clone)]
+pub struct BlobCache {
+    dir: PathBuf,
+    max_size: u64,
+}
+impl BlobCache {
+    /// Opens the cache in the directory, creating it if needed. The total size of blobs is kept under `max_size` bytes.
+    pub fn open(dir: impl AsRef<Path>, max_size: u64) -> Result<Self, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(BlobCache { dir, max_size })
+    }
+    /// Returns the directory of the cache.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    /// Returns the file path of the blob with the hash.
+    pub fn path(&self, hash: &str) -> Result<PathBuf, Error> {
+        if hash.len() != 64 || !hash.bytes().all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f')) {
+            return Err(Error::InvalidKey(hash.to_string()));
+        }
+        Ok(self.dir.join(&hash[..2]).join(hash))
+    }
+    /// Checks if the blob with the hash is stored.
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path(hash).is_ok_and(|path| path.is_file())
+    }
+    /// Stores the blob and returns its SHA-256 as a lowercase hex string. Evicts old blobs if the cache grew too large.
+    pub fn put(&self, content: &[u8]) -> Result<String, Error> {
+        let hash = to_hex(&Sha256::digest(content));
+        let path = self.path(&hash)?;
+
+        // Store the blob unless it is already there
+        if !touch(&path)? {
+            fs::create_dir_all(self.dir.join(&hash[..2]))?;
+            write_atomic(&path, content)?;
+        }
+
+        self.evict()?;
+        Ok(hash)
+    }
+    /// Returns the blob with the hash, or `None` if it is not stored.
+    /// A blob whose content no longer matches its hash is removed and reported as missing.
+    pub fn get(&self, hash: &str) -> Result<Option<Vec<u8>>, Error> {
+        let path = self.path(hash)?;
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        // Drop corrupted blobs
+        if to_hex(&Sha256::digest(&content)) != hash {
+            self.remove(hash)?;
+            return Ok(None);
+        }
+
+        touch(&path)?;
+        Ok(Some(content))
+    }
+    /// Stores the struct serialized in the format and returns the hash of the serialized content.
+    pub fn put_value<F: Format, T: Serialize + ?Sized>(&self, content: &T) -> Result<String, Error> {
+        self.put(&F::to_bytes(content)?)
+    }
+    /// Returns the blob with the hash deserialized from the format, or `None` if it is not stored.
+    pub fn get_value<F: Format, T: for<'de> Deserialize<'de>>(&self, hash: &str) -> Result<Option<T>, Error> {
+        self.get(hash)?.map(|content| F::from_bytes(&content)).transpose()
+    }
+    /// Removes the blob with the hash. Returns `false` if it was not stored.
+    pub fn remove(&self, hash: &str) -> Result<bool, Error> {
+        match fs::remove_file(self.path(hash)?) {
+            Ok(()) => Ok(true),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+    /// Returns the total size of all stored blobs in bytes.
+    pub fn size(&self) -> Result<u64, Error> {
+        Ok(self.blobs()?.iter().map(|(_, size, _)| size).sum())
+    }
+    /// Removes the least recently used blobs until the cache fits its size limit. Returns the number of bytes freed.
+    /// Does nothing if another process is evicting at the same time.
+    pub fn evict(&self) -> Result<u64, Error> {
+        let mut blobs = self.blobs()?;
+        let mut size: u64 = blobs.iter().map(|(_, size, _)| size).sum();
+        if size <= self.max_size {
+            return Ok(0);
+        }
+
+        // Only one process evicts at a time
+        let _lock = match LockFile::acquire(self.dir.join(".lock")) {
+            Ok(lock) => lock,
+            Err(Error::Locked { .. }) => return Ok(0),
+            Err(error) => return Err(error),
+        };
+
+        // Remove the oldest blobs first
+        blobs.sort_by_key(|(_, _, accessed)| *accessed);
+        let mut freed = 0;
+        for (path, blob_size, _) in blobs {
+            if size <= self.max_size {
+                break;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => freed += blob_size,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+                Err(error) => return Err(error.into()),
+            }
+            size -= blob_size;
+        }
+        Ok(freed)
+    }
+    /// Returns the path, size and last access of every stored blob, skipping temporary files and the lock.
+    fn blobs(&self) -> Result<Vec<(PathBuf, u64, SystemTime)>, Error> {
+        let mut blobs = Vec::new();
+        for relative in walk_files(&self.dir)? {
+            if relative.file_name().is_none_or(|name| name.to_string_lossy().starts_with('.')) {
+                continue;
+            }
+            let path = self.dir.join(relative);
+            match fs::metadata(&path) {
+                Ok(metadata) => blobs.push((path, metadata.len(), metadata.modified()?)),
+                // Removed by another process in the meantime
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(blobs)
+    }
+}
+
+/// Marks the blob as used now. Returns `false` if it does not exist.
+fn touch(path: &Path) -> Result<bool, Error> {
+    match fs::File::options().write(true).open(path) {
+        Ok(file) => {
+            file.set_modified(SystemTime::now())?;
+            Ok(true)
+        },
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error.into()),
+    }
+}
@@ -0,0 +1,78 @@
+use std::{fs, marker::PhantomData};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Format, WriteOptions, check_permissions, impl_file_api, write_atomic, write_atomic_with_mode, document::{Document, DocumentFormat}};
+
+#[test]
+fn test() {
+    use std::collections::HashMap;
+    use crate::{Json, Toml};
+
+    let map: HashMap<String, f64> = (0..32).map(|index| (format!("key{index}"), index as f64)).collect();
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.reverse();
+    let other: HashMap<String, f64> = entries.into_iter().map(|(key, value)| (key.clone(), *value)).collect();
+    assert_eq!(Canonical::<Toml>::to_bytes(&map).unwrap(), Canonical::<Toml>::to_bytes(&other).unwrap());
+
+    // Numbers are reformatted regardless of how they were written
+    let path = std::env::temp_dir().join(format!("util_files_canonical_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::write(path, "{\"b\": 1e3, \"a\": -0.0}").unwrap();
+    assert!(!Canonical::<Json>::is_canonical(path).unwrap());
+    Canonical::<Json>::create(path, &Json::load::<serde_json::Value>(path).unwrap()).unwrap();
+    assert!(Canonical::<Json>::is_canonical(path).unwrap());
+    assert_eq!(fs::read_to_string(path).unwrap(), "{\n  \"a\": 0.0,\n  \"b\": 1000.0\n}\n");
+    Canonical::<Json>::save_with(path, &serde_json::json!({ "b": 1e3, "a": [1] }), &WriteOptions::new().compact()).unwrap();
+    assert_eq!(fs::read_to_string(path).unwrap(), "{\"a\":[1],\"b\":1000.0}\n");
+
+    // Numbers beyond the precision of a float are kept as written
+    fs::write(path, "{\"big\": 123456789012345678901234567890, \"exact\": 12.50, \"long\": 0.10000000000000000000001}").unwrap();
+    let value = Json::load::<serde_json::Value>(path).unwrap();
+    assert_eq!(Canonical::<Json>::to_bytes(&value).unwrap(), b"{\n  \"big\": 123456789012345678901234567890,\n  \"exact\": 12.5,\n  \"long\": 0.10000000000000000000001\n}\n");
+    fs::remove_file(path).unwrap();
+}
+
+// #========================#
+// #=== CANONICAL OUTPUT ===#
+
+/// Canonical variant of a format. Map keys are sorted, number formatting is normalized and the output
+/// ends with a single newline, so equal values always produce byte-identical files.
+/// Can be used wherever a [`Format`] is expected, e.g. `DirStore<T, Canonical<Toml>>`.
+///
+/// Only available for TOML and JSON, the formats with a document tree. YAML, RON and bincode have no canonical variant.
+/// JSON numbers a float cannot hold exactly, like integers beyond 64 bits, keep their original text.
+pub struct Canonical<F>(PhantomData<F>);
+impl <F: DocumentFormat> Format for Canonical<F> {
+    const EXTENSION: &'static str = F::EXTENSION;
+    fn to_bytes<T: Serialize + ?Sized>(content: &T) -> Result<Vec<u8>, Error> {
+        let mut document = F::Document::from_struct(content)?;
+        document.canonicalize();
+
+        // Always end with exactly one newline
+        let mut parsed = F::to_bytes(&document)?;
+        while parsed.last() == Some(&b'\n') {
+            parsed.pop();
+        }
+        parsed.push(b'\n');
+        Ok(parsed)
+    }
+    fn from_bytes<T: for<'de> Deserialize<'de>>(content: &[u8]) -> Result<T, Error> {
+        F::from_bytes::<T>(content)
+    }
+    fn to_bytes_with<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<Vec<u8>, Error> {
+        let mut document = F::Document::from_struct(content)?;
+        document.canonicalize();
+
+        // The options decide the layout, the output is still identical for equal values
+        F::to_bytes_with(&document, options)
+    }
+}
+impl <F: DocumentFormat> Canonical<F> {
+    /// Checks if the file is already in canonical form, e.g. to fail CI on files written by hand.
+    pub fn is_canonical(file_path: &str) -> Result<bool, Error> {
+        let content = fs::read(file_path)?;
+        let document = F::from_bytes::<F::Document>(&content)?;
+        Ok(Self::to_bytes(&document)? == content)
+    }
+}
+impl_file_api!([F: DocumentFormat] Canonical<F>, "canonical");
@@ -0,0 +1,162 @@
+use std::{fs, marker::PhantomData, path::{Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Format, Toml, WriteOptions, write_atomic};
+
+#[test]
+fn test() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct User { name: String, age: u32 }
+
+    let dir = std::env::temp_dir().join(format!("util_files_dir_store_{}", std::process::id()));
+    let store = DirStore::<User>::open(&dir).unwrap();
+
+    store.insert("alice/../bob", &User { name: "Bob".into(), age: 30 }).unwrap();
+    store.update("alice/../bob", |user| user.age += 1).unwrap();
+    assert_eq!(store.get("alice/../bob").unwrap().unwrap().age, 31);
+    assert_eq!(store.keys().unwrap(), vec!["alice/../bob".to_string()]);
+
+    // Broken files are reported without aborting the listing
+    fs::write(dir.join("broken.toml"), "age = ").unwrap();
+    let entries: Vec<_> = store.iter().unwrap().collect();
+    assert_eq!(entries.len(), 2);
+    assert!(entries.iter().any(|(key, result)| key == "broken" && result.is_err()));
+
+    assert!(store.remove("alice/../bob").unwrap());
+    assert!(!store.contains("alice/../bob"));
+
+    // Stores opened with options write every value with them
+    let store = DirStore::<User>::open_with(&dir, crate::WriteOptions::new().line_ending(crate::LineEnding::Crlf)).unwrap();
+    store.insert("carol", &User { name: "Carol".into(), age: 20 }).unwrap();
+    assert_eq!(fs::read_to_string(store.path("carol").unwrap()).unwrap(), "name = \"Carol\"\r\nage = 20\r\n");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// Longest encoded key, keeping file names below common file system limits.
+const MAX_ENCODED_KEY: usize = 200;
+
+// #=================#
+// #=== DIR STORE ===#
+
+/// Collection of typed documents stored as one file per key inside a directory.
+/// Keys are arbitrary strings, encoded into safe file names.
+pub struct DirStore<T, F: Format = Toml> {
+    dir: PathBuf,
+    options: Option<WriteOptions>,
+    _marker: PhantomData<fn() -> (T, F)>,
+}
+impl <T: Serialize + for<'de> Deserialize<'de>, F: Format> DirStore<T, F> {
+    /// Opens the store in the directory, creating the directory if it does not exist.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(DirStore { dir: dir.as_ref().to_path_buf(), options: None, _marker: PhantomData })
+    }
+    /// Opens the store in the directory like [`DirStore::open`], writing every value with the options.
+    pub fn open_with(dir: impl AsRef<Path>, options: WriteOptions) -> Result<Self, Error> {
+        let mut store = Self::open(dir)?;
+        store.options = Some(options);
+        Ok(store)
+    }
+    /// Returns the directory of the store.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    /// Returns the path of the file storing the key.
+    pub fn path(&self, key: &str) -> Result<PathBuf, Error> {
+        Ok(self.dir.join(format!("{}.{}", encode_key(key)?, F::EXTENSION)))
+    }
+    /// Returns true if the key is stored.
+    pub fn contains(&self, key: &str) -> bool {
+        self.path(key).is_ok_and(|path| path.is_file())
+    }
+    /// Inserts the value under the key, replacing any existing value.
+    pub fn insert(&self, key: &str, value: &T) -> Result<(), Error> {
+        let parsed = match &self.options {
+            Some(options) => F::to_bytes_with(value, options)?,
+            None => F::to_bytes(value)?,
+        };
+        write_atomic(self.path(key)?, &parsed)
+    }
+    /// Returns the value stored under the key, or `None` if it is not stored.
+    pub fn get(&self, key: &str) -> Result<Option<T>, Error> {
+        match fs::read(self.path(key)?) {
+            Ok(content) => Ok(Some(F::from_bytes(&content)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+    /// Modifies the value stored under the key and returns the updated value.
+    pub fn update(&self, key: &str, modify: impl FnOnce(&mut T)) -> Result<T, Error> {
+        let mut value = self.get(key)?.ok_or_else(|| Error::KeyNotFound(key.to_string()))?;
+        modify(&mut value);
+        self.insert(key, &value)?;
+        Ok(value)
+    }
+    /// Removes the value stored under the key. Returns false if it was not stored.
+    pub fn remove(&self, key: &str) -> Result<bool, Error> {
+        match fs::remove_file(self.path(key)?) {
+            Ok(()) => Ok(true),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(error) => Err(error.into()),
+        }
+    }
+    /// Returns all stored keys in sorted order. Files not created by the store are skipped.
+    pub fn keys(&self) -> Result<Vec<String>, Error> {
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if !path.is_file() || path.extension().is_none_or(|extension| extension != F::EXTENSION) {
+                continue;
+            }
+            if let Some(key) = path.file_stem().and_then(|stem| stem.to_str()).and_then(decode_key) {
+                keys.push(key);
+            }
+        }
+        keys.sort();
+        Ok(keys)
+    }
+    /// Iterates over all stored values in key order.
+    /// Each file is loaded lazily and its error is reported alongside its key, so one broken file does not abort the listing.
+    pub fn iter(&self) -> Result<impl Iterator<Item = (String, Result<T, Error>)> + '_, Error> {
+        Ok(self.keys()?.into_iter().map(|key| {
+            let value = self.get(&key).and_then(|value| value.ok_or_else(|| Error::KeyNotFound(key.clone())));
+            (key, value)
+        }))
+    }
+}
+
+// #========================#
+// #=== KEY SANITISATION ===#
+
+/// Encodes the key into a safe file name. Everything except ASCII letters, digits, `-` and `_` is percent-encoded,
+/// so keys can never contain path separators or start with a dot.
+fn encode_key(key: &str) -> Result<String, Error> {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    if encoded.is_empty() || encoded.len() > MAX_ENCODED_KEY {
+        return Err(Error::InvalidKey(key.to_string()));
+    }
+    Ok(encoded)
+}
+
+/// Decodes a file name created by [`encode_key`]. Returns `None` for names the store did not create.
+fn decode_key(encoded: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iterator = encoded.bytes();
+    while let Some(byte) = iterator.next() {
+        match byte {
+            b'%' => {
+                let hex = [iterator.next()?, iterator.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            },
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => bytes.push(byte),
+            _ => return None,
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
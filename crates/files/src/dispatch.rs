@@ -0,0 +1,174 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::{Bincode, Error, Format, Json, Toml, WriteOptions, write_atomic};
+#[cfg(feature = "ron")]
+use crate::Ron;
+#[cfg(feature = "yaml")]
+use crate::Yaml;
+
+#[test]
+fn test() {
+    let dir = std::env::temp_dir().join(format!("util_files_dispatch_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let value = std::collections::BTreeMap::from([("name".to_string(), "value".to_string())]);
+    for format in FileFormat::ALL {
+        let path = dir.join(format!("config.{}", format.extension()));
+        let path = path.to_str().unwrap();
+        assert_eq!(FileFormat::from_path(path).unwrap(), *format);
+        FileFormat::create(path, &value).unwrap();
+        assert_eq!(FileFormat::load::<std::collections::BTreeMap<String, String>>(path).unwrap(), value);
+        FileFormat::save_with(path, &value, &WriteOptions::new().line_ending(crate::LineEnding::Crlf)).unwrap();
+        assert_eq!(FileFormat::load::<std::collections::BTreeMap<String, String>>(path).unwrap(), value);
+    }
+    assert_eq!(FileFormat::from_extension("TOML"), Some(FileFormat::Toml));
+    assert!(matches!(FileFormat::from_path("config.txt"), Err(Error::UnknownExtension(_))));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+// #===================#
+// #=== FILE FORMAT ===#
+
+/// Format of a file picked at runtime from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileFormat {
+    Toml,
+    Json,
+    Bincode,
+    #[cfg(feature = "yaml")]
+    Yaml,
+    #[cfg(feature = "ron")]
+    Ron,
+}
+impl FileFormat {
+    /// All formats enabled in this build.
+    pub const ALL: &'static [FileFormat] = &[
+        FileFormat::Toml,
+        FileFormat::Json,
+        FileFormat::Bincode,
+        #[cfg(feature = "yaml")]
+        FileFormat::Yaml,
+        #[cfg(feature = "ron")]
+        FileFormat::Ron,
+    ];
+
+    /// Returns the format using the extension (without the leading dot), ignoring case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.to_ascii_lowercase();
+        match extension.as_str() {
+            #[cfg(feature = "yaml")]
+            "yml" => Some(FileFormat::Yaml),
+            _ => Self::ALL.iter().copied().find(|format| format.extension() == extension),
+        }
+    }
+    /// Returns the format of the file from its extension.
+    pub fn from_path(file_path: &str) -> Result<Self, Error> {
+        Path::new(file_path).extension()
+            .and_then(|extension| Self::from_extension(&extension.to_string_lossy()))
+            .ok_or_else(|| Error::UnknownExtension(file_path.to_string()))
+    }
+    /// Returns the default file extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            FileFormat::Toml => Toml::EXTENSION,
+            FileFormat::Json => Json::EXTENSION,
+            FileFormat::Bincode => Bincode::EXTENSION,
+            #[cfg(feature = "yaml")]
+            FileFormat::Yaml => Yaml::EXTENSION,
+            #[cfg(feature = "ron")]
+            FileFormat::Ron => Ron::EXTENSION,
+        }
+    }
+    /// Serializes the struct into the file content of the format.
+    pub fn to_bytes<T: Serialize + ?Sized>(self, content: &T) -> Result<Vec<u8>, Error> {
+        match self {
+            FileFormat::Toml => Toml::to_bytes(content),
+            FileFormat::Json => Json::to_bytes(content),
+            FileFormat::Bincode => Bincode::to_bytes(content),
+            #[cfg(feature = "yaml")]
+            FileFormat::Yaml => Yaml::to_bytes(content),
+            #[cfg(feature = "ron")]
+            FileFormat::Ron => Ron::to_bytes(content),
+        }
+    }
+    /// Serializes the struct into the file content of the format using the write options.
+    pub fn to_bytes_with<T: Serialize + ?Sized>(self, content: &T, options: &WriteOptions) -> Result<Vec<u8>, Error> {
+        match self {
+            FileFormat::Toml => Toml::to_bytes_with(content, options),
+            FileFormat::Json => Json::to_bytes_with(content, options),
+            FileFormat::Bincode => Bincode::to_bytes_with(content, options),
+            #[cfg(feature = "yaml")]
+            FileFormat::Yaml => Yaml::to_bytes_with(content, options),
+            #[cfg(feature = "ron")]
+            FileFormat::Ron => Ron::to_bytes_with(content, options),
+        }
+    }
+    /// Deserializes the file content of the format into the requested struct.
+    pub fn from_bytes<T: for<'de> Deserialize<'de>>(self, content: &[u8]) -> Result<T, Error> {
+        match self {
+            FileFormat::Toml => Toml::from_bytes(content),
+            FileFormat::Json => Json::from_bytes(content),
+            FileFormat::Bincode => Bincode::from_bytes(content),
+            #[cfg(feature = "yaml")]
+            FileFormat::Yaml => Yaml::from_bytes(content),
+            #[cfg(feature = "ron")]
+            FileFormat::Ron => Ron::from_bytes(content),
+        }
+    }
+    /// Tries to create a new file from the struct provided, in the format matching its extension.
+    pub fn create<T: Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+        let parsed = Self::from_path(file_path)?.to_bytes(content)?;
+        write_atomic(file_path, &parsed)
+    }
+    /// Tries to create a new file from the struct provided, in the format matching its extension and written with the options.
+    pub fn create_with<T: Serialize>(file_path: &str, content: &T, options: &WriteOptions) -> Result<(), Error> {
+        let parsed = Self::from_path(file_path)?.to_bytes_with(content, options)?;
+        write_atomic(file_path, &parsed)
+    }
+    /// Tries to save the struct to an existing file, in the format matching its extension.
+    pub fn save<T: Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+        std::fs::metadata(file_path)?;
+        Self::create(file_path, content)
+    }
+    /// Tries to save the struct to an existing file, in the format matching its extension and written with the options.
+    pub fn save_with<T: Serialize>(file_path: &str, content: &T, options: &WriteOptions) -> Result<(), Error> {
+        std::fs::metadata(file_path)?;
+        Self::create_with(file_path, content, options)
+    }
+    /// Tries to load a file into the required struct, in the format matching its extension.
+    pub fn load<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+        let format = Self::from_path(file_path)?;
+        format.from_bytes(&std::fs::read(file_path)?)
+    }
+    /// Async version of [`FileFormat::create`] using non-blocking file IO.
+    #[cfg(feature = "tokio")]
+    pub async fn create_async<T: Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+        let parsed = Self::from_path(file_path)?.to_bytes(content)?;
+        crate::write_atomic_async(file_path, &parsed, None).await
+    }
+    /// Async version of [`FileFormat::create_with`] using non-blocking file IO.
+    #[cfg(feature = "tokio")]
+    pub async fn create_with_async<T: Serialize>(file_path: &str, content: &T, options: &WriteOptions) -> Result<(), Error> {
+        let parsed = Self::from_path(file_path)?.to_bytes_with(content, options)?;
+        crate::write_atomic_async(file_path, &parsed, None).await
+    }
+    /// Async version of [`FileFormat::save`] using non-blocking file IO.
+    #[cfg(feature = "tokio")]
+    pub async fn save_async<T: Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+        tokio::fs::metadata(file_path).await?;
+        Self::create_async(file_path, content).await
+    }
+    /// Async version of [`FileFormat::save_with`] using non-blocking file IO.
+    #[cfg(feature = "tokio")]
+    pub async fn save_with_async<T: Serialize>(file_path: &str, content: &T, options: &WriteOptions) -> Result<(), Error> {
+        tokio::fs::metadata(file_path).await?;
+        Self::create_with_async(file_path, content, options).await
+    }
+    /// Async version of [`FileFormat::load`] using non-blocking file IO.
+    #[cfg(feature = "tokio")]
+    pub async fn load_async<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+        let format = Self::from_path(file_path)?;
+        format.from_bytes(&tokio::fs::read(file_path).await?)
+    }
+}
@@ -0,0 +1,641 @@
+use std::fs;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Format, Json, Toml, WriteOptions, write_atomic};
+
+#[test]
+fn test() {
+    let path = std::env::temp_dir().join(format!("util_files_document_{}.toml", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::write(path, "# Server settings\n[server]\nport = 80 # public port\nhosts = [\"a\", \"b\"]\nname = \"main\"\n").unwrap();
+
+    Toml::set_key(path, "server.port", "8080").unwrap();
+    Toml::set_key(path, "server.hosts[1]", "c").unwrap();
+    assert_eq!(Toml::get_key(path, "server.port").unwrap().as_integer(), Some(8080));
+    assert_eq!(Toml::get_key(path, "server.hosts[1]").unwrap().as_str(), Some("c"));
+    assert!(matches!(Toml::set_key(path, "server.port", "abc"), Err(Error::TypeMismatch { .. })));
+    assert_eq!(fs::read_to_string(path).unwrap(), "# Server settings\n[server]\nport = 8080 # public port\nhosts = [\"a\", \"c\"]\nname = \"main\"\n");
+
+    Toml::remove_key(path, "server.hosts").unwrap();
+    assert!(matches!(Toml::get_key(path, "server.hosts"), Err(Error::KeyNotFound(_))));
+    assert!(fs::read_to_string(path).unwrap().starts_with("# Server settings\n[server]\nport = 8080 # public port\n"));
+
+    // Symbolic links are written through instead of being replaced
+    #[cfg(unix)]
+    {
+        let link = path.replace(".toml", "_link.toml");
+        std::os::unix::fs::symlink(path, &link).unwrap();
+        Toml::set_key(&link, "server.port", "9090").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(Toml::get_key(path, "server.port").unwrap().as_integer(), Some(9090));
+        Toml::save(&link, &toml::toml! { port = 1 }).unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(path).unwrap(), "port = 1\n");
+        fs::remove_file(link).unwrap();
+    }
+    fs::remove_file(path).unwrap();
+
+    // JSON is rewritten as a whole, with the options if provided
+    let path = path.replace(".toml", ".json");
+    fs::write(&path, "{\"a\": 1}").unwrap();
+    Json::set_key_with(&path, "b", "2", &WriteOptions::new().compact()).unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{\"a\":1,\"b\":2}\n");
+    fs::remove_file(&path).unwrap();
+}
+
+// #================#
+// #=== KEY PATH ===#
+
+/// Single step of a dotted key path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// Key of a table or object
+    Key(String),
+    /// Index into an array
+    Index(usize),
+}
+impl From<&str> for Segment {
+    fn from(value: &str) -> Self {
+        Segment::Key(value.to_string())
+    }
+}
+
+/// Parses a dotted key path with array indices, e.g. `server.hosts[0].name`.
+pub(crate) fn parse_key_path(key_path: &str) -> Result<Vec<Segment>, Error> {
+    let invalid = || Error::InvalidKeyPath(key_path.to_string());
+    let mut segments = Vec::new();
+
+    for part in key_path.split('.') {
+        // Split the key from the trailing indices
+        let (key, mut rest) = part.split_at(part.find('[').unwrap_or(part.len()));
+        if key.is_empty() && (segments.is_empty() || rest.is_empty()) {
+            return Err(invalid());
+        }
+        if !key.is_empty() {
+            segments.push(Segment::Key(key.to_string()));
+        }
+
+        // Parse all indices following the key
+        while !rest.is_empty() {
+            let end = rest.find(']').ok_or_else(invalid)?;
+            let index = rest[1..end].parse::<usize>().map_err(|_| invalid())?;
+            segments.push(Segment::Index(index));
+            rest = &rest[end + 1..];
+            if !rest.is_empty() && !rest.starts_with('[') {
+                return Err(invalid());
+            }
+        }
+    }
+    Ok(segments)
+}
+
+/// Formats the segments back into a dotted key path.
+pub(crate) fn format_key_path(segments: &[Segment]) -> String {
+    let mut key_path = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Key(key) if key_path.is_empty() => key_path.push_str(key),
+            Segment::Key(key) => { key_path.push('.'); key_path.push_str(key); },
+            Segment::Index(index) => key_path.push_str(&format!("[{index}]")),
+        }
+    }
+    key_path
+}
+
+// #=======================#
+// #=== DOCUMENT VALUES ===#
+
+/// Untyped document tree of a self-describing format.
+/// Public only to be usable in bounds, the module is private so the trait cannot be named outside of the crate.
+pub trait Document: Sized + Clone + Serialize + for<'de> Deserialize<'de> {
+    /// Returns the name of the value type, used in error messages.
+    fn type_name(&self) -> &'static str;
+    /// Returns all children of a table or array.
+    fn children(&self) -> Vec<(Segment, &Self)>;
+    /// Returns the child at the segment.
+    fn child(&self, segment: &Segment) -> Option<&Self>;
+    /// Returns the mutable child at the segment.
+    fn child_mut(&mut self, segment: &Segment) -> Option<&mut Self>;
+    /// Inserts a new child into a table. Returns false if this is not a table.
+    fn insert_child(&mut self, segment: &Segment, value: Self) -> bool;
+    /// Removes the child at the segment.
+    fn remove_child(&mut self, segment: &Segment) -> Option<Self>;
+    /// Parses the raw string into a value of the same type as this one.
+    fn parse_like(&self, raw: &str) -> Option<Self>;
+    /// Parses the raw string into a value of inferred type.
+    fn parse_any(raw: &str) -> Self;
+    /// Returns the string if this value is a string.
+    fn as_string(&self) -> Option<&str>;
+    /// Creates a string value.
+    fn from_string(value: String) -> Self;
+    /// Returns an empty table.
+    fn empty_table() -> Self;
+    /// Returns true if this value is a table.
+    fn is_table(&self) -> bool;
+    /// Returns the keys of this table, empty if this is not a table.
+    fn keys(&self) -> Vec<String>;
+    /// Deep-merges the other value over this one. Tables are merged recursively, everything else is replaced.
+    fn merge(&mut self, other: Self);
+    /// Serializes the struct into a value.
+    fn from_struct<T: Serialize + ?Sized>(content: &T) -> Result<Self, Error>;
+    /// Deserializes the value into the requested struct.
+    fn into_struct<T: for<'de> Deserialize<'de>>(self) -> Result<T, Error>;
+    /// Deserializes the value into the requested struct, reporting every key the struct did not consume.
+    fn into_struct_ignored<T: for<'de> Deserialize<'de>>(self, callback: impl FnMut(serde_ignored::Path)) -> Result<T, Error>;
+    /// Sorts all table keys and normalizes number formatting, so equal values always serialize the same.
+    fn canonicalize(&mut self);
+}
+
+/// Format with an untyped document tree.
+pub trait DocumentFormat: Format {
+    /// Document tree of the format
+    type Document: Document;
+}
+impl DocumentFormat for Toml {
+    type Document = toml::Value;
+}
+impl DocumentFormat for Json {
+    type Document = serde_json::Value;
+}
+
+impl Document for toml::Value {
+    fn type_name(&self) -> &'static str {
+        self.type_str()
+    }
+    fn children(&self) -> Vec<(Segment, &Self)> {
+        match self {
+            toml::Value::Table(table) => table.iter().map(|(key, value)| (Segment::Key(key.clone()), value)).collect(),
+            toml::Value::Array(array) => array.iter().enumerate().map(|(index, value)| (Segment::Index(index), value)).collect(),
+            _ => Vec::new(),
+        }
+    }
+    fn child(&self, segment: &Segment) -> Option<&Self> {
+        match segment {
+            Segment::Key(key) => self.as_table()?.get(key),
+            Segment::Index(index) => self.as_array()?.get(*index),
+        }
+    }
+    fn child_mut(&mut self, segment: &Segment) -> Option<&mut Self> {
+        match segment {
+            Segment::Key(key) => self.as_table_mut()?.get_mut(key),
+            Segment::Index(index) => self.as_array_mut()?.get_mut(*index),
+        }
+    }
+    fn insert_child(&mut self, segment: &Segment, value: Self) -> bool {
+        match (self, segment) {
+            (toml::Value::Table(table), Segment::Key(key)) => table.insert(key.clone(), value).is_none(),
+            _ => false,
+        }
+    }
+    fn remove_child(&mut self, segment: &Segment) -> Option<Self> {
+        match (self, segment) {
+            (toml::Value::Table(table), Segment::Key(key)) => table.remove(key),
+            (toml::Value::Array(array), Segment::Index(index)) if *index < array.len() => Some(array.remove(*index)),
+            _ => None,
+        }
+    }
+    fn parse_like(&self, raw: &str) -> Option<Self> {
+        match self {
+            toml::Value::String(_) => Some(toml::Value::String(raw.to_string())),
+            toml::Value::Integer(_) => raw.trim().parse().ok().map(toml::Value::Integer),
+            toml::Value::Float(_) => raw.trim().parse().ok().map(toml::Value::Float),
+            toml::Value::Boolean(_) => raw.trim().parse().ok().map(toml::Value::Boolean),
+            toml::Value::Datetime(_) => raw.trim().parse().ok().map(toml::Value::Datetime),
+            toml::Value::Array(_) | toml::Value::Table(_) => parse_toml_inline(raw).filter(|value| value.same_type(self)),
+        }
+    }
+    fn parse_any(raw: &str) -> Self {
+        parse_toml_inline(raw).unwrap_or_else(|| toml::Value::String(raw.to_string()))
+    }
+    fn as_string(&self) -> Option<&str> {
+        self.as_str()
+    }
+    fn from_string(value: String) -> Self {
+        toml::Value::String(value)
+    }
+    fn empty_table() -> Self {
+        toml::Value::Table(toml::Table::new())
+    }
+    fn is_table(&self) -> bool {
+        self.is_table()
+    }
+    fn keys(&self) -> Vec<String> {
+        self.as_table().map(|table| table.keys().cloned().collect()).unwrap_or_default()
+    }
+    fn merge(&mut self, other: Self) {
+        match (self, other) {
+            (toml::Value::Table(table), toml::Value::Table(other)) => {
+                for (key, value) in other {
+                    match table.get_mut(&key) {
+                        Some(existing) => existing.merge(value),
+                        None => { table.insert(key, value); },
+                    }
+                }
+            },
+            (this, other) => *this = other,
+        }
+    }
+    fn from_struct<T: Serialize + ?Sized>(content: &T) -> Result<Self, Error> {
+        Ok(toml::Value::try_from(content)?)
+    }
+    fn into_struct<T: for<'de> Deserialize<'de>>(self) -> Result<T, Error> {
+        Ok(self.try_into::<T>()?)
+    }
+    fn into_struct_ignored<T: for<'de> Deserialize<'de>>(self, callback: impl FnMut(serde_ignored::Path)) -> Result<T, Error> {
+        Ok(serde_ignored::deserialize(self, callback)?)
+    }
+    fn canonicalize(&mut self) {
+        match self {
+            toml::Value::Table(table) => {
+                let mut entries: Vec<_> = std::mem::take(table).into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                for (key, mut value) in entries {
+                    value.canonicalize();
+                    table.insert(key, value);
+                }
+            },
+            toml::Value::Array(array) => array.iter_mut().for_each(Document::canonicalize),
+            // Negative zero equals zero, so it must serialize the same
+            toml::Value::Float(float) if *float == 0.0 => *float = 0.0,
+            _ => {},
+        }
+    }
+}
+
+/// Parses a TOML inline value, e.g. `8080`, `[1, 2]` or `{ a = 1 }`.
+fn parse_toml_inline(raw: &str) -> Option<toml::Value> {
+    toml::from_str::<toml::Table>(&format!("value = {raw}")).ok()?.remove("value")
+}
+
+impl Document for serde_json::Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            serde_json::Value::Null => "null",
+            serde_json::Value::Bool(_) => "boolean",
+            serde_json::Value::Number(number) if number.is_f64() => "float",
+            serde_json::Value::Number(_) => "integer",
+            serde_json::Value::String(_) => "string",
+            serde_json::Value::Array(_) => "array",
+            serde_json::Value::Object(_) => "object",
+        }
+    }
+    fn children(&self) -> Vec<(Segment, &Self)> {
+        match self {
+            serde_json::Value::Object(object) => object.iter().map(|(key, value)| (Segment::Key(key.clone()), value)).collect(),
+            serde_json::Value::Array(array) => array.iter().enumerate().map(|(index, value)| (Segment::Index(index), value)).collect(),
+            _ => Vec::new(),
+        }
+    }
+    fn child(&self, segment: &Segment) -> Option<&Self> {
+        match segment {
+            Segment::Key(key) => self.as_object()?.get(key),
+            Segment::Index(index) => self.as_array()?.get(*index),
+        }
+    }
+    fn child_mut(&mut self, segment: &Segment) -> Option<&mut Self> {
+        match segment {
+            Segment::Key(key) => self.as_object_mut()?.get_mut(key),
+            Segment::Index(index) => self.as_array_mut()?.get_mut(*index),
+        }
+    }
+    fn insert_child(&mut self, segment: &Segment, value: Self) -> bool {
+        match (self, segment) {
+            (serde_json::Value::Object(object), Segment::Key(key)) => object.insert(key.clone(), value).is_none(),
+            _ => false,
+        }
+    }
+    fn remove_child(&mut self, segment: &Segment) -> Option<Self> {
+        match (self, segment) {
+            (serde_json::Value::Object(object), Segment::Key(key)) => object.remove(key),
+            (serde_json::Value::Array(array), Segment::Index(index)) if *index < array.len() => Some(array.remove(*index)),
+            _ => None,
+        }
+    }
+    fn parse_like(&self, raw: &str) -> Option<Self> {
+        match self {
+            serde_json::Value::Null => Some(Self::parse_any(raw)),
+            serde_json::Value::String(_) => Some(serde_json::Value::String(raw.to_string())),
+            serde_json::Value::Number(number) if number.is_f64() => serde_json::from_str::<serde_json::Value>(raw).ok().filter(|value| value.is_number()),
+            _ => serde_json::from_str::<serde_json::Value>(raw).ok().filter(|value| value.type_name() == self.type_name()),
+        }
+    }
+    fn parse_any(raw: &str) -> Self {
+        serde_json::from_str(raw).unwrap_or_else(|_| serde_json::Value::String(raw.to_string()))
+    }
+    fn as_string(&self) -> Option<&str> {
+        self.as_str()
+    }
+    fn from_string(value: String) -> Self {
+        serde_json::Value::String(value)
+    }
+    fn empty_table() -> Self {
+        serde_json::Value::Object(serde_json::Map::new())
+    }
+    fn is_table(&self) -> bool {
+        self.is_object()
+    }
+    fn keys(&self) -> Vec<String> {
+        self.as_object().map(|object| object.keys().cloned().collect()).unwrap_or_default()
+    }
+    fn merge(&mut self, other: Self) {
+        match (self, other) {
+            (serde_json::Value::Object(object), serde_json::Value::Object(other)) => {
+                for (key, value) in other {
+                    match object.get_mut(&key) {
+                        Some(existing) => existing.merge(value),
+                        None => { object.insert(key, value); },
+                    }
+                }
+            },
+            (this, other) => *this = other,
+        }
+    }
+    fn from_struct<T: Serialize + ?Sized>(content: &T) -> Result<Self, Error> {
+        Ok(serde_json::to_value(content)?)
+    }
+    fn into_struct<T: for<'de> Deserialize<'de>>(self) -> Result<T, Error> {
+        Ok(serde_json::from_value::<T>(self)?)
+    }
+    fn into_struct_ignored<T: for<'de> Deserialize<'de>>(self, callback: impl FnMut(serde_ignored::Path)) -> Result<T, Error> {
+        Ok(serde_ignored::deserialize(self, callback)?)
+    }
+    fn canonicalize(&mut self) {
+        match self {
+            serde_json::Value::Object(object) => {
+                let mut entries: Vec<_> = std::mem::take(object).into_iter().collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                for (key, mut value) in entries {
+                    value.canonicalize();
+                    object.insert(key, value);
+                }
+            },
+            serde_json::Value::Array(array) => array.iter_mut().for_each(Document::canonicalize),
+            // Numbers keep their original text, so `1e3` and `1000.0` must be reformatted
+            serde_json::Value::Number(number) => {
+                let normalized = if let Some(integer) = number.as_i64() {
+                    Some(serde_json::Number::from(integer))
+                } else if let Some(integer) = number.as_u64() {
+                    Some(serde_json::Number::from(integer))
+                } else {
+                    // Numbers a float cannot hold exactly, like huge integers, keep their original text
+                    number.as_f64()
+                        .filter(|float| decimal_digits(&number.to_string()) == decimal_digits(&format!("{float:e}")))
+                        .map(|float| if float == 0.0 { 0.0 } else { float })
+                        .and_then(serde_json::Number::from_f64)
+                };
+                if let Some(normalized) = normalized {
+                    *number = normalized;
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Returns the significant digits and exponent of a decimal number, so equal values compare equal however they are written.
+/// Zero has no digits and its sign is ignored.
+fn decimal_digits(text: &str) -> Option<(bool, String, i64)> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (mantissa, exponent) = text.split_once(['e', 'E']).unwrap_or((text, "0"));
+    let mut exponent: i64 = exponent.parse().ok()?;
+
+    // Move the decimal point behind the last digit
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    exponent -= fraction.len() as i64;
+    let digits = format!("{integer}{fraction}");
+    if !digits.chars().all(|char| char.is_ascii_digit()) {
+        return None;
+    }
+
+    // Strip zeros that do not change the value
+    let digits = digits.trim_start_matches('0');
+    let trimmed = digits.trim_end_matches('0');
+    exponent += (digits.len() - trimmed.len()) as i64;
+    match trimmed.is_empty() {
+        true => Some((false, String::new(), 0)),
+        false => Some((negative, trimmed.to_string(), exponent)),
+    }
+}
+
+// #===========================#
+// #=== KEY PATH OPERATIONS ===#
+
+/// Walks the segments down from the root.
+pub(crate) fn walk<'a, D: Document>(mut node: &'a mut D, segments: &[Segment], key_path: &str) -> Result<&'a mut D, Error> {
+    for segment in segments {
+        node = node.child_mut(segment).ok_or_else(|| Error::KeyNotFound(key_path.to_string()))?;
+    }
+    Ok(node)
+}
+
+/// Reads the document from the file.
+pub(crate) fn read_document<F: Format, D: Document>(file_path: &str) -> Result<D, Error> {
+    F::from_bytes::<D>(&fs::read(file_path)?)
+}
+
+/// Returns the value at the key path.
+fn get_key<F: Format, D: Document>(file_path: &str, key_path: &str) -> Result<D, Error> {
+    let segments = parse_key_path(key_path)?;
+    let mut document = read_document::<F, D>(file_path)?;
+
+    // Detach the value from the document
+    let (last, parents) = segments.split_last().ok_or_else(|| Error::InvalidKeyPath(key_path.to_string()))?;
+    walk(&mut document, parents, key_path)?.remove_child(last).ok_or_else(|| Error::KeyNotFound(key_path.to_string()))
+}
+
+/// Sets the value at the key path in the document, type-checked against the existing value. Returns the new value.
+fn set_value<D: Document>(document: &mut D, segments: &[Segment], key_path: &str, raw: &str) -> Result<D, Error> {
+    let (last, parents) = segments.split_last().ok_or_else(|| Error::InvalidKeyPath(key_path.to_string()))?;
+    let parent = walk(document, parents, key_path)?;
+    match parent.child_mut(last) {
+        // Replace the existing value with one of the same type
+        Some(existing) => {
+            *existing = existing.parse_like(raw).ok_or_else(|| Error::TypeMismatch {
+                path: key_path.to_string(),
+                expected: existing.type_name(),
+                value: raw.to_string(),
+            })?;
+        },
+        // Insert a new key into the parent table
+        None => {
+            if !parent.insert_child(last, D::parse_any(raw)) {
+                return Err(Error::KeyNotFound(key_path.to_string()));
+            }
+        },
+    }
+    parent.child(last).cloned().ok_or_else(|| Error::KeyNotFound(key_path.to_string()))
+}
+
+/// Removes the value at the key path from the document and returns it.
+fn remove_value<D: Document>(document: &mut D, segments: &[Segment], key_path: &str) -> Result<D, Error> {
+    let (last, parents) = segments.split_last().ok_or_else(|| Error::InvalidKeyPath(key_path.to_string()))?;
+    walk(document, parents, key_path)?.remove_child(last).ok_or_else(|| Error::KeyNotFound(key_path.to_string()))
+}
+
+/// Serializes the edited document, with the options if provided.
+pub(crate) fn document_bytes<F: Format, D: Document>(document: &D, options: Option<&WriteOptions>) -> Result<Vec<u8>, Error> {
+    match options {
+        Some(options) => F::to_bytes_with(document, options),
+        None => F::to_bytes(document),
+    }
+}
+
+/// Sets the value at the key path, type-checked against the existing value.
+fn set_key<F: Format, D: Document>(file_path: &str, key_path: &str, raw: &str, options: Option<&WriteOptions>) -> Result<(), Error> {
+    let segments = parse_key_path(key_path)?;
+    let mut document = read_document::<F, D>(file_path)?;
+    set_value(&mut document, &segments, key_path, raw)?;
+    write_atomic(file_path, &document_bytes::<F, D>(&document, options)?)
+}
+
+/// Removes the value at the key path and returns it.
+fn remove_key<F: Format, D: Document>(file_path: &str, key_path: &str, options: Option<&WriteOptions>) -> Result<D, Error> {
+    let segments = parse_key_path(key_path)?;
+    let mut document = read_document::<F, D>(file_path)?;
+    let removed = remove_value(&mut document, &segments, key_path)?;
+    write_atomic(file_path, &document_bytes::<F, D>(&document, options)?)?;
+    Ok(removed)
+}
+
+// #====================#
+// #=== TOML EDITING ===#
+
+/// Node of an editable TOML document that can hold children.
+enum TomlNode<'a> {
+    Table(&'a mut dyn toml_edit::TableLike),
+    Array(&'a mut toml_edit::Array),
+    Tables(&'a mut toml_edit::ArrayOfTables),
+}
+impl <'a> TomlNode<'a> {
+    /// Returns the node of the item, if it can hold children.
+    fn from_item(item: &'a mut toml_edit::Item) -> Option<Self> {
+        match item {
+            toml_edit::Item::Table(table) => Some(TomlNode::Table(table)),
+            toml_edit::Item::ArrayOfTables(tables) => Some(TomlNode::Tables(tables)),
+            toml_edit::Item::Value(value) => Self::from_value(value),
+            toml_edit::Item::None => None,
+        }
+    }
+    /// Returns the node of the value, if it can hold children.
+    fn from_value(value: &'a mut toml_edit::Value) -> Option<Self> {
+        match value {
+            toml_edit::Value::InlineTable(table) => Some(TomlNode::Table(table)),
+            toml_edit::Value::Array(array) => Some(TomlNode::Array(array)),
+            _ => None,
+        }
+    }
+    /// Returns the child node at the segment.
+    fn child(self, segment: &Segment) -> Option<Self> {
+        match (self, segment) {
+            (TomlNode::Table(table), Segment::Key(key)) => table.get_mut(key).and_then(Self::from_item),
+            (TomlNode::Array(array), Segment::Index(index)) => array.get_mut(*index).and_then(Self::from_value),
+            (TomlNode::Tables(tables), Segment::Index(index)) => tables.get_mut(*index).map(|table| TomlNode::Table(table)),
+            _ => None,
+        }
+    }
+}
+
+/// Replaces the value, keeping the whitespace and comments around it.
+fn replace_decorated(existing: &mut toml_edit::Value, mut value: toml_edit::Value) {
+    *value.decor_mut() = existing.decor().clone();
+    *existing = value;
+}
+
+/// Reads the TOML file, applies the edit to the parent of the key path and writes it back.
+/// Everything the edit does not touch keeps its formatting, order and comments.
+fn edit_toml(file_path: &str, segments: &[Segment], key_path: &str, edit: impl FnOnce(TomlNode, &Segment) -> Option<()>) -> Result<(), Error> {
+    let mut document = fs::read_to_string(file_path)?.parse::<toml_edit::DocumentMut>()?;
+
+    // Walk down to the parent of the edited value
+    let (last, parents) = segments.split_last().ok_or_else(|| Error::InvalidKeyPath(key_path.to_string()))?;
+    let mut node = TomlNode::Table(document.as_table_mut());
+    for segment in parents {
+        node = node.child(segment).ok_or_else(|| Error::KeyNotFound(key_path.to_string()))?;
+    }
+    edit(node, last).ok_or_else(|| Error::KeyNotFound(key_path.to_string()))?;
+
+    write_atomic(file_path, document.to_string().as_bytes())
+}
+
+/// Sets the value at the key path of a TOML file, preserving the formatting and comments of the file.
+fn set_toml_key(file_path: &str, key_path: &str, raw: &str) -> Result<(), Error> {
+    // Validate and parse the value on the plain document first
+    let segments = parse_key_path(key_path)?;
+    let value = set_value(&mut read_document::<Toml, toml::Value>(file_path)?, &segments, key_path, raw)?;
+    let value = value.to_string().parse::<toml_edit::Value>()?;
+
+    edit_toml(file_path, &segments, key_path, |parent, last| {
+        match (parent, last) {
+            (TomlNode::Table(table), Segment::Key(key)) => match table.get_mut(key) {
+                Some(toml_edit::Item::Value(existing)) => replace_decorated(existing, value),
+                Some(item) => *item = toml_edit::Item::Value(value),
+                None => { table.insert(key, toml_edit::Item::Value(value)); },
+            },
+            (TomlNode::Array(array), Segment::Index(index)) => replace_decorated(array.get_mut(*index)?, value),
+            _ => return None,
+        }
+        Some(())
+    })
+}
+
+/// Removes the value at the key path of a TOML file, preserving the formatting and comments of the file.
+fn remove_toml_key(file_path: &str, key_path: &str) -> Result<toml::Value, Error> {
+    let segments = parse_key_path(key_path)?;
+    let removed = remove_value(&mut read_document::<Toml, toml::Value>(file_path)?, &segments, key_path)?;
+
+    edit_toml(file_path, &segments, key_path, |parent, last| {
+        match (parent, last) {
+            (TomlNode::Table(table), Segment::Key(key)) => { table.remove(key)?; },
+            (TomlNode::Array(array), Segment::Index(index)) if *index < array.len() => { array.remove(*index); },
+            (TomlNode::Tables(tables), Segment::Index(index)) if *index < tables.len() => { tables.remove(*index); },
+            _ => return None,
+        }
+        Some(())
+    })?;
+    Ok(removed)
+}
+
+impl Toml {
+    /// Tries to read the value at the dotted key path (e.g. `server.hosts[0]`) from a TOML file.
+    pub fn get_key(file_path: &str, key_path: &str) -> Result<toml::Value, Error> {
+        get_key::<Self, toml::Value>(file_path, key_path)
+    }
+    /// Tries to set the value at the dotted key path in a TOML file.
+    /// The raw value is parsed as the type of the existing value, new keys have their type inferred.
+    /// The rest of the file keeps its formatting, key order and comments, so [`WriteOptions`] do not apply.
+    pub fn set_key(file_path: &str, key_path: &str, value: &str) -> Result<(), Error> {
+        set_toml_key(file_path, key_path, value)
+    }
+    /// Tries to remove the value at the dotted key path from a TOML file, returning the removed value.
+    /// The rest of the file keeps its formatting, key order and comments, so [`WriteOptions`] do not apply.
+    pub fn remove_key(file_path: &str, key_path: &str) -> Result<toml::Value, Error> {
+        remove_toml_key(file_path, key_path)
+    }
+}
+
+impl Json {
+    /// Tries to read the value at the dotted key path (e.g. `server.hosts[0]`) from a JSON file.
+    pub fn get_key(file_path: &str, key_path: &str) -> Result<serde_json::Value, Error> {
+        get_key::<Self, serde_json::Value>(file_path, key_path)
+    }
+    /// Tries to set the value at the dotted key path in a JSON file.
+    /// The raw value is parsed as the type of the existing value, new keys have their type inferred.
+    pub fn set_key(file_path: &str, key_path: &str, value: &str) -> Result<(), Error> {
+        set_key::<Self, serde_json::Value>(file_path, key_path, value, None)
+    }
+    /// Tries to set the value at the dotted key path in a JSON file, rewriting the file with the options.
+    pub fn set_key_with(file_path: &str, key_path: &str, value: &str, options: &WriteOptions) -> Result<(), Error> {
+        set_key::<Self, serde_json::Value>(file_path, key_path, value, Some(options))
+    }
+    /// Tries to remove the value at the dotted key path from a JSON file, returning the removed value.
+    pub fn remove_key(file_path: &str, key_path: &str) -> Result<serde_json::Value, Error> {
+        remove_key::<Self, serde_json::Value>(file_path, key_path, None)
+    }
+    /// Tries to remove the value at the dotted key path from a JSON file, rewriting the file with the options.
+    pub fn remove_key_with(file_path: &str, key_path: &str, options: &WriteOptions) -> Result<serde_json::Value, Error> {
+        remove_key::<Self, serde_json::Value>(file_path, key_path, Some(options))
+    }
+}
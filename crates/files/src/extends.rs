@@ -0,0 +1,92 @@
+use std::{fs, path::PathBuf};
+use serde::Deserialize;
+
+use crate::{Error, Format, Json, Toml, document::{Document, read_document}};
+
+#[test]
+fn test() {
+    #[derive(Deserialize, Debug)]
+    struct Config { port: u16, host: String, tags: Vec<String> }
+
+    let dir = crate::TempDir::new().unwrap();
+    fs::create_dir_all(dir.join("project")).unwrap();
+    fs::write(dir.join("base.toml"), "port = 80\nhost = \"localhost\"\ntags = [\"base\"]\n").unwrap();
+    fs::write(dir.join("team.toml"), "extends = \"base.toml\"\nport = 8080\n").unwrap();
+    fs::write(dir.join("project/app.toml"), "extends = \"../team.toml\"\ntags = [\"app\"]\n").unwrap();
+
+    let config = Toml::load_extended::<Config>(dir.join("project/app.toml").to_str().unwrap()).unwrap();
+    assert_eq!((config.port, config.host.as_str(), config.tags), (8080, "localhost", vec!["app".to_string()]));
+
+    // Cycles are reported with the whole chain
+    fs::write(dir.join("base.toml"), "extends = \"project/app.toml\"\n").unwrap();
+    let error = Toml::load_extended::<Config>(dir.join("project/app.toml").to_str().unwrap()).unwrap_err();
+    assert!(matches!(&error, Error::ExtendsCycle { chain } if chain.len() == 4));
+}
+
+/// Top-level key naming the file a config extends, relative to the file itself.
+pub const EXTENDS_KEY: &str = "extends";
+
+/// Loads the file and all files it extends, deep-merging every parent under its child.
+fn load_extended<F: Format, D: Document, T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+    let mut chain: Vec<String> = Vec::new();
+    let mut visited: Vec<PathBuf> = Vec::new();
+    let mut documents: Vec<D> = Vec::new();
+    let mut current = PathBuf::from(file_path);
+    loop {
+        chain.push(current.to_string_lossy().into_owned());
+
+        // Read the next file of the chain, reporting the chain if a parent fails
+        let result = (|| {
+            let canonical = fs::canonicalize(&current)?;
+            if visited.contains(&canonical) {
+                return Err(Error::ExtendsCycle { chain: chain.clone() });
+            }
+            let document = read_document::<F, D>(&current.to_string_lossy())?;
+            Ok((canonical, document))
+        })();
+        let (canonical, mut document) = match result {
+            Ok(loaded) => loaded,
+            Err(error @ Error::ExtendsCycle { .. }) => return Err(error),
+            Err(error) if chain.len() == 1 => return Err(error),
+            Err(error) => return Err(Error::Extends { chain, error: Box::new(error) }),
+        };
+
+        // Follow the extends key relative to the file
+        let parent = match document.remove_child(&EXTENDS_KEY.into()) {
+            Some(parent) => match parent.as_string() {
+                Some(parent) => Some(canonical.parent().map(|dir| dir.join(parent)).unwrap_or_else(|| PathBuf::from(parent))),
+                None => return Err(Error::TypeMismatch { path: EXTENDS_KEY.to_string(), expected: "string", value: parent.type_name().to_string() }),
+            },
+            None => None,
+        };
+        visited.push(canonical);
+        documents.push(document);
+        match parent {
+            Some(parent) => current = parent,
+            None => break,
+        }
+    }
+
+    // Merge from the root of the chain down to the loaded file
+    let mut merged = documents.pop().unwrap_or_else(D::empty_table);
+    while let Some(child) = documents.pop() {
+        merged.merge(child);
+    }
+    merged.into_struct::<T>()
+}
+
+impl Toml {
+    /// Tries to load a TOML file into the required struct, following its `extends = "path"` key.
+    /// Extended files are loaded recursively and deep-merged under the file extending them.
+    pub fn load_extended<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+        load_extended::<Self, toml::Value, T>(file_path)
+    }
+}
+
+impl Json {
+    /// Tries to load a JSON file into the required struct, following its `"extends": "path"` key.
+    /// Extended files are loaded recursively and deep-merged under the file extending them.
+    pub fn load_extended<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+        load_extended::<Self, serde_json::Value, T>(file_path)
+    }
+}
@@ -0,0 +1,229 @@
+use std::{fs, io::Read, path::{Component, Path, PathBuf}};
+
+use crate::{Error, walk_files};
+
+#[test]
+fn test() {
+    let dir = crate::TempDir::new().unwrap();
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("a.json"), " {\"a\": [1, 2]}\n").unwrap();
+    fs::write(dir.join("nested/b.toml"), "[server]\nport = 80\n").unwrap();
+    fs::write(dir.join("nested/c.gz"), [0x1f, 0x8b, 8, 0]).unwrap();
+
+    assert_eq!(dir_size(dir.path()).unwrap(), 15 + 19 + 4);
+    assert_eq!(detect_file_type(dir.join("a.json")).unwrap(), FileType::Json);
+    assert_eq!(detect_file_type(dir.join("nested/b.toml")).unwrap(), FileType::Toml);
+    assert_eq!(detect_file_type(dir.join("nested/c.gz")).unwrap(), FileType::Gzip);
+    assert_eq!(FileType::detect(b"PK\x03\x04rest"), FileType::Zip);
+    assert_eq!(FileType::detect(&[BINCODE_MAGIC.as_slice(), &[1, 2]].concat()), FileType::Bincode);
+    crate::Bincode::create_with_header(dir.join("d.bin").to_str().unwrap(), &(1u32, "a")).unwrap();
+    assert_eq!(detect_file_type(dir.join("d.bin")).unwrap(), FileType::Bincode);
+    assert_eq!(crate::Bincode::load_with_header::<(u32, String)>(dir.join("d.bin").to_str().unwrap()).unwrap(), (1, "a".to_string()));
+    assert!(matches!(crate::Bincode::load_with_header::<u32>(dir.join("a.json").to_str().unwrap()), Err(Error::BincodeHeader)));
+    fs::remove_file(dir.join("d.bin")).unwrap();
+    assert_eq!(FileType::detect(&[0, 159, 146, 150]), FileType::Unknown);
+
+    assert_eq!(format_bytes(512, ByteUnits::Binary), "512 B");
+    assert_eq!(format_bytes(1536, ByteUnits::Binary), "1.5 KiB");
+    assert_eq!(format_bytes(2_500_000, ByteUnits::Decimal), "2.5 MB");
+    assert_eq!(format_bytes(1_048_575, ByteUnits::Binary), "1 MiB");
+    assert_eq!(format_bytes(999_999, ByteUnits::Decimal), "1 MB");
+    assert_eq!(FileType::detect(b"# only a comment\n"), FileType::Unknown);
+
+    assert_eq!(relative_path("/srv/app/config/a.toml", "/srv/app"), PathBuf::from("config/a.toml"));
+    assert_eq!(relative_path("/srv/data/../logs/x.log", "/srv/app/"), PathBuf::from("../logs/x.log"));
+    assert_eq!(relative_path("/srv/app", "/srv/app"), PathBuf::from("."));
+    assert_eq!(relative_path("../../x", "."), PathBuf::from("../../x"));
+}
+
+/// Magic bytes at the start of bincode files written by [`Bincode::create_with_header`](crate::Bincode::create_with_header).
+pub const BINCODE_MAGIC: &[u8; 4] = b"BINC";
+
+/// Number of bytes read from the start of a file to detect its type.
+const DETECT_LIMIT: u64 = 64 * 1024;
+
+// #=================#
+// #=== DIR SIZES ===#
+
+/// Returns the total size in bytes of all regular files under the directory.
+/// Symbolic links are skipped, so the size never includes files outside the directory.
+pub fn dir_size(dir: impl AsRef<Path>) -> Result<u64, Error> {
+    let dir = dir.as_ref();
+    let mut size = 0;
+    for relative in walk_files(dir)? {
+        size += fs::symlink_metadata(dir.join(relative))?.len();
+    }
+    Ok(size)
+}
+
+// #==================#
+// #=== BYTE UNITS ===#
+
+/// Unit system used to format byte counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ByteUnits {
+    /// Powers of 1024: KiB, MiB, GiB, ...
+    #[default]
+    Binary,
+    /// Powers of 1000: KB, MB, GB, ...
+    Decimal,
+}
+
+/// Formats the byte count for humans, e.g. `1.5 KiB` or `2.5 MB`. Counts below one unit are printed exactly.
+pub fn format_bytes(bytes: u64, units: ByteUnits) -> String {
+    let (base, names): (f64, [&str; 6]) = match units {
+        ByteUnits::Binary => (1024.0, ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+        ByteUnits::Decimal => (1000.0, ["KB", "MB", "GB", "TB", "PB", "EB"]),
+    };
+    if (bytes as f64) < base {
+        return format!("{bytes} B");
+    }
+
+    // Find the largest unit keeping the value at least one
+    let mut value = bytes as f64 / base;
+    let mut unit = 0;
+    while value >= base && unit < names.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+
+    // Rounding may reach the next unit, e.g. 1023.95 KiB
+    if (value * 10.0).round() / 10.0 >= base && unit < names.len() - 1 {
+        value /= base;
+        unit += 1;
+    }
+    let formatted = format!("{value:.1}");
+    format!("{} {}", formatted.strip_suffix(".0").unwrap_or(&formatted), names[unit])
+}
+
+// #=================#
+// #=== FILE TYPE ===#
+
+/// Type of a file detected from its content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileType {
+    /// Zip archive, including formats built on it like JAR or DOCX
+    Zip,
+    /// Gzip compressed data, e.g. `.gz` or `.tar.gz`
+    Gzip,
+    /// JSON object or array
+    Json,
+    /// TOML document with at least one key or table
+    Toml,
+    /// Bincode starting with [`BINCODE_MAGIC`], plain bincode has no header and cannot be detected
+    Bincode,
+    /// Anything else, including empty files and plain bincode
+    Unknown,
+}
+impl FileType {
+    /// Detects the type from the whole content of a file. JSON and TOML are validated by parsing them.
+    pub fn detect(content: &[u8]) -> Self {
+        Self::detect_partial(content, true)
+    }
+    /// Detects the type from the content. Cut off JSON and TOML are recognized by how they start instead.
+    fn detect_partial(content: &[u8], complete: bool) -> Self {
+        // Binary formats are recognized by their magic bytes
+        if content.starts_with(b"PK\x03\x04") || content.starts_with(b"PK\x05\x06") {
+            return FileType::Zip;
+        }
+        if content.starts_with(&[0x1f, 0x8b]) {
+            return FileType::Gzip;
+        }
+        if content.starts_with(BINCODE_MAGIC) {
+            return FileType::Bincode;
+        }
+
+        // Text formats must be UTF-8, allowing a character cut off at the end
+        let text = match std::str::from_utf8(content) {
+            Ok(text) => text,
+            Err(error) if !complete && error.error_len().is_none() => std::str::from_utf8(&content[..error.valid_up_to()]).unwrap_or_default(),
+            Err(_) => return FileType::Unknown,
+        };
+        let text = text.trim_start_matches('\u{feff}');
+        let trimmed = text.trim_start();
+
+        // Complete content is validated by parsing it
+        if complete {
+            if (trimmed.starts_with('{') || trimmed.starts_with('[')) && serde_json::from_str::<serde::de::IgnoredAny>(text).is_ok() {
+                return FileType::Json;
+            }
+            if text.parse::<toml::Table>().is_ok_and(|table| !table.is_empty()) {
+                return FileType::Toml;
+            }
+            return FileType::Unknown;
+        }
+
+        // Cut off content is recognized by how it starts
+        if trimmed.starts_with('{') || trimmed.starts_with('[') && !is_toml_header(trimmed) {
+            return FileType::Json;
+        }
+        if is_toml_header(trimmed) || trimmed.lines().next().is_some_and(|line| line.contains(" = ")) {
+            return FileType::Toml;
+        }
+        FileType::Unknown
+    }
+}
+
+/// Checks if the text starts with a TOML table header like `[server]` or `[[items]]`.
+fn is_toml_header(text: &str) -> bool {
+    let line = text.lines().next().unwrap_or_default().trim_end();
+    let name = line.trim_start_matches('[').trim_end_matches(']');
+    line.starts_with('[') && line.ends_with(']') && !name.is_empty()
+        && name.chars().all(|char| char.is_alphanumeric() || matches!(char, '_' | '-' | '.' | '"' | '\'' | ' '))
+}
+
+/// Detects the type of the file from its content, reading at most its first 64 KiB.
+pub fn detect_file_type(file_path: impl AsRef<Path>) -> Result<FileType, Error> {
+    let file = fs::File::open(file_path)?;
+    let size = file.metadata()?.len();
+    let mut content = Vec::new();
+    file.take(DETECT_LIMIT).read_to_end(&mut content)?;
+    Ok(FileType::detect_partial(&content, size <= DETECT_LIMIT))
+}
+
+// #======================#
+// #=== RELATIVE PATHS ===#
+
+/// Removes `.` and resolves `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => { normalized.pop(); },
+                Some(Component::RootDir | Component::Prefix(_)) => {},
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Returns the path relative to the base for display, using `..` to leave the base if needed.
+/// Works lexically without touching the file system, so symbolic links are not resolved.
+/// Returns the path unchanged if it cannot be expressed relative to the base, e.g. on another drive.
+pub fn relative_path(path: impl AsRef<Path>, base: impl AsRef<Path>) -> PathBuf {
+    let (path, base) = (normalize(path.as_ref()), normalize(base.as_ref()));
+    if path.is_absolute() != base.is_absolute() {
+        return path;
+    }
+
+    // Skip the shared prefix, then climb out of the rest of the base
+    let mut path_components = path.components().peekable();
+    let mut base_components = base.components().peekable();
+    while let (Some(a), Some(b)) = (path_components.peek(), base_components.peek()) && a == b {
+        path_components.next();
+        base_components.next();
+    }
+    if base_components.peek().is_some_and(|component| matches!(component, Component::Prefix(_) | Component::RootDir | Component::ParentDir)) {
+        return path;
+    }
+    let mut relative: PathBuf = base_components.map(|_| Component::ParentDir).collect();
+    relative.extend(path_components);
+    if relative.as_os_str().is_empty() {
+        relative.push(".");
+    }
+    relative
+}
@@ -0,0 +1,262 @@
+use std::{collections::BTreeMap, fs, io::{Read, Write}, path::{Path, PathBuf}, time::{Duration, Instant}};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Bincode, Error, Format, LockFile, write_atomic};
+
+#[test]
+fn test() {
+    let dir = crate::TempDir::new().unwrap();
+    let options = KvOptions::new().compact_after(3);
+
+    let mut store = KvStore::<u32>::open_with(dir.path(), options.clone()).unwrap();
+    assert!(matches!(KvStore::<u32>::open(dir.path()), Err(Error::Locked { .. })));
+    store.insert("a", 1).unwrap();
+    store.insert("b", 2).unwrap();
+    assert_eq!(store.remove("a").unwrap(), Some(1));
+    assert!(dir.join(SNAPSHOT_FILE).exists());
+    store.insert("c", 3).unwrap();
+    drop(store);
+
+    // Simulate a crash in the middle of appending a record
+    fs::OpenOptions::new().append(true).open(dir.join(LOG_FILE)).unwrap().write_all(&[9, 0, 0, 0, 1]).unwrap();
+    let store = KvStore::<u32>::open_with(dir.path(), options.clone()).unwrap();
+    assert_eq!(store.iter().collect::<Vec<_>>(), vec![("b", &2), ("c", &3)]);
+    drop(store);
+
+    // Records of another value type are reported without touching the log
+    let length = fs::metadata(dir.join(LOG_FILE)).unwrap().len();
+    assert!(matches!(KvStore::<String>::open_with(dir.path(), options.clone()), Err(Error::BincodeDecode(_))));
+    assert_eq!(fs::metadata(dir.join(LOG_FILE)).unwrap().len(), length);
+    assert_eq!(KvStore::<u32>::open_with(dir.path(), options).unwrap().len(), 2);
+}
+
+/// File holding the compacted state of the store.
+const SNAPSHOT_FILE: &str = "snapshot.bin";
+/// File holding the changes made since the last compaction.
+const LOG_FILE: &str = "log.bin";
+/// File guarding the store against use by multiple processes.
+const LOCK_FILE: &str = "store.lock";
+
+// #===============#
+// #=== OPTIONS ===#
+
+/// When appended changes are flushed to disk with `fsync`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncPolicy {
+    /// Sync after every change, nothing is lost on power failure
+    #[default]
+    Always,
+    /// Sync at most once per interval, changes made since the last sync may be lost on power failure
+    Interval(Duration),
+    /// Leave syncing to the operating system, only process crashes are survived
+    Never,
+}
+
+/// Options for opening a [`KvStore`].
+#[derive(Debug, Clone)]
+pub struct KvOptions {
+    sync: SyncPolicy,
+    compact_after: u64,
+}
+impl Default for KvOptions {
+    fn default() -> Self {
+        KvOptions { sync: SyncPolicy::Always, compact_after: 1000 }
+    }
+}
+impl KvOptions {
+    /// Creates the default options, syncing every change and compacting after 1000 changes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets when changes are synced to disk.
+    pub fn sync(mut self, sync: SyncPolicy) -> Self {
+        self.sync = sync;
+        self
+    }
+    /// Sets the number of logged changes after which the log is compacted into the snapshot.
+    pub fn compact_after(mut self, changes: u64) -> Self {
+        self.compact_after = changes.max(1);
+        self
+    }
+}
+
+// #===========#
+// #=== LOG ===#
+
+/// Single change recorded in the log.
+#[derive(Serialize, Deserialize)]
+enum LogRecord<K, V> {
+    Insert(K, V),
+    Remove(K),
+}
+
+/// Returns the checksum stored with every log record.
+fn checksum(payload: &[u8]) -> [u8; 4] {
+    let hash = Sha256::digest(payload);
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Replays the log over the entries. Returns the number of valid records and the length of the valid prefix.
+/// Reading stops at the first truncated record or checksum mismatch, which is where a crash interrupted the last append.
+/// Intact records that fail to decode are not crash damage, e.g. the store was opened with the wrong value type, and are returned as errors.
+fn replay<V: for<'de> Deserialize<'de>>(content: &[u8], entries: &mut BTreeMap<String, V>) -> Result<(u64, u64), Error> {
+    let (mut records, mut offset) = (0, 0);
+    while let Some(header) = content.get(offset..offset + 8) {
+        let length = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let Some(payload) = content.get(offset + 8..offset + 8 + length) else { break };
+        if checksum(payload) != header[4..8] {
+            break;
+        }
+        match Bincode::from_bytes::<LogRecord<String, V>>(payload)? {
+            LogRecord::Insert(key, value) => { entries.insert(key, value); },
+            LogRecord::Remove(key) => { entries.remove(&key); },
+        }
+        records += 1;
+        offset += 8 + length;
+    }
+    Ok((records, offset as u64))
+}
+
+// #================#
+// #=== KV STORE ===#
+
+/// Persistent key-value store kept in a directory, with all entries held in memory.
+///
+/// Every change is appended to a log, which is periodically compacted into a bincode snapshot.
+/// On open the log is replayed over the snapshot, dropping a partially written record left by a crash.
+/// The store is guarded by a [`LockFile`], so only one process can open it at a time.
+#[derive(Debug)]
+pub struct KvStore<V> {
+    dir: PathBuf,
+    options: KvOptions,
+    entries: BTreeMap<String, V>,
+    log: fs::File,
+    logged: u64,
+    last_sync: Instant,
+    _lock: LockFile,
+}
+impl <V: Serialize + for<'de> Deserialize<'de>> KvStore<V> {
+    /// Opens the store in the directory with the default options, creating it if needed.
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::open_with(dir, KvOptions::default())
+    }
+    /// Opens the store in the directory with the options, creating it if needed and recovering from a crash.
+    pub fn open_with(dir: impl AsRef<Path>, options: KvOptions) -> Result<Self, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let lock = LockFile::acquire(dir.join(LOCK_FILE))?;
+
+        // Load the snapshot
+        let mut entries = match fs::read(dir.join(SNAPSHOT_FILE)) {
+            Ok(content) => Bincode::from_bytes::<BTreeMap<String, V>>(&content)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(error) => return Err(error.into()),
+        };
+
+        // Replay the log and cut off a partially written record, the log is left untouched if a record fails to decode
+        let mut log = fs::OpenOptions::new().read(true).append(true).create(true).open(dir.join(LOG_FILE))?;
+        let mut content = Vec::new();
+        log.read_to_end(&mut content)?;
+        let (logged, valid) = replay(&content, &mut entries)?;
+        if valid < content.len() as u64 {
+            log.set_len(valid)?;
+            log.sync_all()?;
+        }
+
+        Ok(KvStore { dir, options, entries, log, logged, last_sync: Instant::now(), _lock: lock })
+    }
+    /// Returns the directory of the store.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    /// Returns the value stored under the key.
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.entries.get(key)
+    }
+    /// Checks if a value is stored under the key.
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+    /// Stores the value under the key, returning the previous value.
+    pub fn insert(&mut self, key: impl Into<String>, value: V) -> Result<Option<V>, Error> {
+        let key = key.into();
+        self.append(&LogRecord::Insert(key.as_str(), &value))?;
+        let previous = self.entries.insert(key, value);
+        self.compact_if_needed()?;
+        Ok(previous)
+    }
+    /// Removes the value stored under the key, returning it.
+    pub fn remove(&mut self, key: &str) -> Result<Option<V>, Error> {
+        if !self.entries.contains_key(key) {
+            return Ok(None);
+        }
+        self.append(&LogRecord::<_, &V>::Remove(key))?;
+        let previous = self.entries.remove(key);
+        self.compact_if_needed()?;
+        Ok(previous)
+    }
+    /// Returns all keys in sorted order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+    /// Returns all entries sorted by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &V)> {
+        self.entries.iter().map(|(key, value)| (key.as_str(), value))
+    }
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    /// Checks if the store holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Writes all entries into a new snapshot and clears the log.
+    pub fn compact(&mut self) -> Result<(), Error> {
+        // The snapshot replaces the old one atomically, a crash before clearing the log only replays it again
+        write_atomic(self.dir.join(SNAPSHOT_FILE), &Bincode::to_bytes(&self.entries)?)?;
+        self.log.set_len(0)?;
+        self.log.sync_all()?;
+        self.logged = 0;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+    /// Flushes all logged changes to disk, regardless of the sync policy.
+    pub fn sync(&mut self) -> Result<(), Error> {
+        self.log.sync_data()?;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+    /// Appends the record to the log and syncs it according to the policy.
+    fn append(&mut self, record: &LogRecord<&str, &V>) -> Result<(), Error> {
+        let payload = Bincode::to_bytes(record)?;
+        let length = u32::try_from(payload.len()).map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "log record is too large"))?;
+
+        // Write the record in one call, so a crash leaves at most one partial record
+        let mut frame = Vec::with_capacity(payload.len() + 8);
+        frame.extend_from_slice(&length.to_le_bytes());
+        frame.extend_from_slice(&checksum(&payload));
+        frame.extend_from_slice(&payload);
+        self.log.write_all(&frame)?;
+        self.logged += 1;
+
+        match self.options.sync {
+            SyncPolicy::Always => self.sync(),
+            SyncPolicy::Interval(interval) if self.last_sync.elapsed() >= interval => self.sync(),
+            SyncPolicy::Interval(_) | SyncPolicy::Never => Ok(()),
+        }
+    }
+    /// Compacts the log once it holds enough changes.
+    fn compact_if_needed(&mut self) -> Result<(), Error> {
+        if self.logged >= self.options.compact_after {
+            self.compact()?;
+        }
+        Ok(())
+    }
+}
+impl <V> Drop for KvStore<V> {
+    fn drop(&mut self) {
+        let _ = self.log.sync_data();
+    }
+}
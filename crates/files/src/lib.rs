@@ -1,8 +1,61 @@
-use std::{fs, io::Write};
+use std::{fs, io::Write, path::{Path, PathBuf}, sync::atomic::{AtomicUsize, Ordering}};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-/// The errors that could happen when loading a TOML file.
+#[cfg(feature = "tokio")]
+mod async_fs;
+mod base_dir;
+mod blob_cache;
+mod canonical;
+mod dir_store;
+mod dispatch;
+mod document;
+mod extends;
+mod inspect;
+mod kv_store;
+mod lock;
+mod manifest;
+mod permissions;
+mod profile;
+#[cfg(feature = "reqwest")]
+mod remote;
+mod secret;
+mod secrets;
+mod stream;
+mod strict;
+mod tabular;
+mod temp;
+mod transaction;
+mod transfer;
+mod watcher;
+mod write_options;
+
+#[cfg(feature = "tokio")]
+pub use async_fs::{check_permissions_async, write_atomic_async};
+pub use base_dir::BaseDir;
+pub use blob_cache::BlobCache;
+pub use canonical::Canonical;
+pub use dir_store::DirStore;
+pub use dispatch::FileFormat;
+pub use extends::EXTENDS_KEY;
+pub use inspect::{BINCODE_MAGIC, ByteUnits, FileType, detect_file_type, dir_size, format_bytes, relative_path};
+pub use kv_store::{KvOptions, KvStore, SyncPolicy};
+pub use lock::LockFile;
+pub use manifest::{Manifest, ManifestEntry, ManifestReport};
+pub use permissions::check_permissions;
+pub use profile::DEFAULT_PROFILE;
+pub use secret::Secret;
+pub use secrets::{ENCRYPTED_PREFIX, SecretKey};
+pub use stream::JsonArrayIter;
+pub use strict::{KeyWarning, Strict, StrictMode};
+pub use tabular::{Csv, CsvOptions, CsvQuoting};
+pub use temp::{TempDir, TempFile};
+pub use transaction::Transaction;
+pub use transfer::{Compare, Progress, TransferOptions, copy_dir, move_dir, sync_dir};
+pub use watcher::{WatchEvent, WatchEventKind, WatchOptions, Watcher};
+pub use write_options::{ArrayLayout, LineEnding, WriteOptions};
+
+/// The errors that could happen when working with files.
 #[derive(Debug, Error)]
 pub enum Error {
     /// Failed to interact with the file system
@@ -16,6 +69,123 @@ pub enum Error {
     /// Failed to deserialize the TOML into the requested struct
     #[error("Failed to deserialize the TOML into the requested struct due to {0}")]
     Deserialize (toml::de::Error),
+
+    /// Failed to serialize or deserialize JSON
+    #[error("Failed to serialize or deserialize JSON due to {0}")]
+    Json (serde_json::Error),
+
+    /// Failed to serialize the provided struct to bincode
+    #[error("Failed to serialize the provided struct to bincode due to {0}")]
+    BincodeEncode (bincode::error::EncodeError),
+
+    /// Failed to deserialize the bincode into the requested struct
+    #[error("Failed to deserialize the bincode into the requested struct due to {0}")]
+    BincodeDecode (bincode::error::DecodeError),
+
+    /// The bincode file does not start with [`BINCODE_MAGIC`]
+    #[error("The bincode file does not start with the expected header")]
+    BincodeHeader,
+
+    /// Failed to read or write CSV
+    #[error("Failed to read or write CSV due to {0}")]
+    Csv (csv::Error),
+
+    /// Failed to read or deserialize a single CSV row, `line` is the 1-based file line the row starts on
+    #[error("Failed to read the CSV row on line {line} due to {error}")]
+    CsvRow { line: u64, error: csv::Error },
+
+    /// Failed to parse the TOML file for editing
+    #[error("Failed to parse the TOML file for editing due to {0}")]
+    TomlEdit (toml_edit::TomlError),
+
+    /// The provided key path could not be parsed
+    #[error("The key path `{0}` is not valid")]
+    InvalidKeyPath (String),
+
+    /// The provided key path does not exist in the document
+    #[error("The key path `{0}` does not exist in the document")]
+    KeyNotFound (String),
+
+    /// The new value does not match the type of the existing value
+    #[error("The value `{value}` is not a valid {expected} for the key path `{path}`")]
+    TypeMismatch { path: String, expected: &'static str, value: String },
+
+    /// The requested profile does not exist in the file
+    #[error("The profile `{profile}` does not exist, available profiles are: {}", .available.join(", "))]
+    UnknownProfile { profile: String, available: Vec<String> },
+
+    /// The files extend each other in a cycle
+    #[error("The extends chain forms a cycle: {}", .chain.join(" -> "))]
+    ExtendsCycle { chain: Vec<String> },
+
+    /// A file extended by the loaded file failed to load
+    #[error("Failed to load the extends chain {} due to {error}", .chain.join(" -> "))]
+    Extends { chain: Vec<String>, error: Box<Error> },
+
+    /// Strict loading found unknown or deprecated keys
+    #[error("The file contains unknown or deprecated keys: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    StrictKeys (Vec<KeyWarning>),
+
+    /// Failed to encrypt or decrypt a secret value
+    #[error("Failed to encrypt or decrypt a secret value due to {0}")]
+    Encryption (String),
+
+    /// A sensitive file is accessible by group or others
+    #[error("The sensitive file `{path}` has insecure permissions {mode:o}, it must not be accessible by group or others")]
+    InsecurePermissions { path: String, mode: u32 },
+
+    /// A sensitive file is owned by another user
+    #[error("The sensitive file `{path}` is owned by user {owner} instead of the current user")]
+    InsecureOwner { path: String, owner: u32 },
+
+    /// The provided key cannot be used to name a file
+    #[error("The key `{0}` cannot be used to name a file")]
+    InvalidKey (String),
+
+    /// The provided glob pattern could not be parsed
+    #[error("Failed to parse the glob pattern due to {0}")]
+    Glob (glob::PatternError),
+
+    /// Failed to watch a directory for changes
+    #[error("Failed to watch a directory for changes due to {0}")]
+    Watch (notify::Error),
+
+    /// The path resolves outside of the base directory
+    #[error("The path `{0}` escapes the base directory")]
+    PathEscape (String),
+
+    /// The lock file is held by another live process
+    #[error("The lock file `{path}` is held by process {pid}")]
+    Locked { path: String, pid: u32 },
+
+    /// The file extension does not belong to any enabled format
+    #[error("The file `{0}` has no extension of an enabled format")]
+    UnknownExtension (String),
+
+    /// Failed to fetch a file over HTTP
+    #[cfg(feature = "reqwest")]
+    #[error("Failed to fetch a file over HTTP due to {0}")]
+    Http (reqwest::Error),
+
+    /// The server answered 304 Not Modified although there is no cached copy to use
+    #[cfg(feature = "reqwest")]
+    #[error("The server answered `{0}` with 304 Not Modified, but there is no cached copy")]
+    NotModified (String),
+
+    /// Failed to serialize or deserialize YAML
+    #[cfg(feature = "yaml")]
+    #[error("Failed to process YAML due to {0}")]
+    Yaml (serde_norway::Error),
+
+    /// Failed to serialize RON
+    #[cfg(feature = "ron")]
+    #[error("Failed to serialize RON due to {0}")]
+    RonSerialize (ron::Error),
+
+    /// Failed to deserialize RON
+    #[cfg(feature = "ron")]
+    #[error("Failed to deserialize RON due to {0}")]
+    RonDeserialize (ron::error::SpannedError),
 }
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
@@ -32,62 +202,502 @@ impl From<toml::de::Error> for Error {
         Error::Deserialize(value)
     }
 }
+impl From<bincode::error::EncodeError> for Error {
+    fn from(value: bincode::error::EncodeError) -> Self {
+        Error::BincodeEncode(value)
+    }
+}
+impl From<bincode::error::DecodeError> for Error {
+    fn from(value: bincode::error::DecodeError) -> Self {
+        Error::BincodeDecode(value)
+    }
+}
+impl From<csv::Error> for Error {
+    fn from(value: csv::Error) -> Self {
+        Error::Csv(value)
+    }
+}
+impl From<toml_edit::TomlError> for Error {
+    fn from(value: toml_edit::TomlError) -> Self {
+        Error::TomlEdit(value)
+    }
+}
+impl From<glob::PatternError> for Error {
+    fn from(value: glob::PatternError) -> Self {
+        Error::Glob(value)
+    }
+}
+impl From<notify::Error> for Error {
+    fn from(value: notify::Error) -> Self {
+        Error::Watch(value)
+    }
+}
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Error::Json(value)
+    }
+}
+#[cfg(feature = "reqwest")]
+impl From<reqwest::Error> for Error {
+    fn from(value: reqwest::Error) -> Self {
+        Error::Http(value)
+    }
+}
+#[cfg(feature = "yaml")]
+impl From<serde_norway::Error> for Error {
+    fn from(value: serde_norway::Error) -> Self {
+        Error::Yaml(value)
+    }
+}
+#[cfg(feature = "ron")]
+impl From<ron::Error> for Error {
+    fn from(value: ron::Error) -> Self {
+        Error::RonSerialize(value)
+    }
+}
+#[cfg(feature = "ron")]
+impl From<ron::error::SpannedError> for Error {
+    fn from(value: ron::error::SpannedError) -> Self {
+        Error::RonDeserialize(value)
+    }
+}
+
+// #===================#
+// #=== FILE SYSTEM ===#
+
+/// Counter making temporary file names unique within the process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns a unique temporary path next to the provided file.
+pub(crate) fn temp_sibling(file_path: &Path) -> PathBuf {
+    let name = file_path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let count = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    file_path.with_file_name(format!(".{name}.{}.{count}.tmp", std::process::id()))
+}
+
+/// Writes the content into a temporary file next to the target and renames it over the target.
+/// Readers will either see the old or the new content, never a partially written file.
+/// Symbolic links are written through, and the permissions and owner (where allowed) of an already existing target are preserved.
+pub fn write_atomic(file_path: impl AsRef<Path>, content: &[u8]) -> Result<(), Error> {
+    write_atomic_with_mode(file_path, content, None)
+}
+
+/// Same as [`write_atomic`], but the file gets the provided Unix mode (e.g. `0o600`) instead.
+/// The mode is ignored on other platforms.
+pub fn write_atomic_with_mode(file_path: impl AsRef<Path>, content: &[u8], mode: Option<u32>) -> Result<(), Error> {
+    let file_path = resolve_symlink(file_path.as_ref())?;
+    let file_path = file_path.as_path();
+    let temp_path = temp_sibling(file_path);
+
+    let result = (|| {
+        // Create the temporary file, never readable by others if a mode is requested
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, mode);
+        }
+        let mut file = options.open(&temp_path)?;
+
+        // Apply the requested permissions or preserve the existing ones
+        match mode {
+            #[cfg(unix)]
+            Some(mode) => file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(mode))?,
+            _ => if let Ok(metadata) = fs::metadata(file_path) {
+                file.set_permissions(metadata.permissions())?;
+                preserve_owner(&file, &metadata);
+            },
+        }
+
+        // Write the content into the temporary file
+        file.write_all(content)?;
+        file.sync_all()?;
+
+        // Replace the target with the temporary file and persist the rename
+        fs::rename(&temp_path, file_path)?;
+        sync_parent(file_path)
+    })();
+
+    // Clean up the temporary file if anything failed
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    Ok(result?)
+}
+
+/// Maximum number of symbolic links followed when resolving a path, matching common kernel limits.
+const MAX_SYMLINKS: usize = 40;
+
+/// Follows the path while it is a symbolic link, so writes replace the file it points to instead of the link.
+/// Dangling links resolve to the missing file they point to.
+pub(crate) fn resolve_symlink(file_path: &Path) -> std::io::Result<PathBuf> {
+    let mut resolved = file_path.to_path_buf();
+    for _ in 0..MAX_SYMLINKS {
+        match fs::symlink_metadata(&resolved) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                let link = fs::read_link(&resolved)?;
+                resolved = resolved.parent().map_or_else(|| link.clone(), |parent| parent.join(&link));
+            },
+            _ => return Ok(resolved),
+        }
+    }
+    Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("too many levels of symbolic links in {}", file_path.display())))
+}
+
+/// Gives the new file the owner and group of the file it replaces. Changes the process is not allowed to make are skipped.
+#[cfg(unix)]
+pub(crate) fn preserve_owner(file: &impl std::os::fd::AsFd, metadata: &fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+    let _ = std::os::unix::fs::fchown(file, Some(metadata.uid()), Some(metadata.gid()));
+}
+
+/// Owners are only preserved on Unix.
+#[cfg(not(unix))]
+pub(crate) fn preserve_owner<F>(_file: &F, _metadata: &fs::Metadata) {}
+
+/// Syncs the directory holding the file, so a rename inside it survives a power failure.
+/// Only needed and possible on Unix.
+pub(crate) fn sync_parent(file_path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let parent = file_path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        fs::File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = file_path;
+    Ok(())
+}
+
+/// Returns the paths of all regular files under the directory relative to it, sorted.
+/// Symbolic links are skipped, so the walk never leaves the directory.
+pub(crate) fn walk_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        for entry in fs::read_dir(dir.join(&relative))? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(relative.join(entry.file_name()));
+            } else if file_type.is_file() {
+                files.push(relative.join(entry.file_name()));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// #========================#
+// #=== FORMAT INTERFACE ===#
+
+/// Trait implemented by all supported file formats.
+pub trait Format {
+    /// File extension used by this format, without the leading dot.
+    const EXTENSION: &'static str;
+    /// Serializes the struct into the file content.
+    fn to_bytes<T: Serialize + ?Sized>(content: &T) -> Result<Vec<u8>, Error>;
+    /// Deserializes the file content into the requested struct.
+    fn from_bytes<T: for<'de> Deserialize<'de>>(content: &[u8]) -> Result<T, Error>;
+    /// Serializes the struct into the file content using the write options.
+    /// Formats without configurable output, like binary ones, ignore the options.
+    fn to_bytes_with<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<Vec<u8>, Error> {
+        let _ = options;
+        Self::to_bytes(content)
+    }
+}
+
+/// Implements the shared get/create/save/load methods for a format.
+macro_rules! impl_file_api {
+    ($format:ident, $name:literal) => {
+        impl_file_api!([] $format, $name);
+    };
+    ([$($generics:tt)*] $format:ty, $name:literal) => {
+        impl<$($generics)*> $format {
+            #[doc = concat!("Tries to load a ", $name, " file from path. If it doesn't find one, it creates one from default.")]
+            pub fn get<T:for<'de> Deserialize<'de> + Serialize + Default>(file_path: &str) -> Result<T, Error> {
+                // Create the config if it does not exist
+                if !fs::exists(file_path)? {
+                    Self::create_default::<T>(file_path)?;
+                }
+
+                // Try to load the config file
+                Self::load::<T>(file_path)
+            }
+            #[doc = concat!("Tries to create a new ", $name, " file from the struct provided.")]
+            pub fn create<T:Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes(content)?;
+
+                // Write the content to the file
+                write_atomic(file_path, &parsed)
+            }
+            #[doc = concat!("Tries to create a new ", $name, " file from the struct provided, written with the options.")]
+            pub fn create_with<T:Serialize>(file_path: &str, content: &T, options: &WriteOptions) -> Result<(), Error> {
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes_with(content, options)?;
+
+                // Write the content to the file
+                write_atomic(file_path, &parsed)
+            }
+            #[doc = concat!("Tries to create a new ", $name, " file from the struct provided with the Unix mode (e.g. `0o600`).")]
+            pub fn create_with_mode<T:Serialize>(file_path: &str, content: &T, mode: u32) -> Result<(), Error> {
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes(content)?;
+
+                // Write the content to the file
+                write_atomic_with_mode(file_path, &parsed, Some(mode))
+            }
+            #[doc = concat!("Tries to create a new ", $name, " file from struct default.")]
+            pub fn create_default<T:Default + Serialize>(file_path: &str) -> Result<(), Error> {
+                Self::create(file_path, &T::default())
+            }
+            #[doc = concat!("Tries to save the struct to an existing ", $name, " file.")]
+            pub fn save<T:Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+                // Make sure the file exists or return with error
+                fs::metadata(file_path)?;
+
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes(content)?;
+
+                // Write the content to the file
+                write_atomic(file_path, &parsed)
+            }
+            #[doc = concat!("Tries to save the struct to an existing ", $name, " file, written with the options.")]
+            pub fn save_with<T:Serialize>(file_path: &str, content: &T, options: &WriteOptions) -> Result<(), Error> {
+                // Make sure the file exists or return with error
+                fs::metadata(file_path)?;
+
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes_with(content, options)?;
+
+                // Write the content to the file
+                write_atomic(file_path, &parsed)
+            }
+            #[doc = concat!("Tries to load a ", $name, " file into the required struct.")]
+            pub fn load<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+                // Load the file or return with error
+                let content = fs::read(file_path)?;
+
+                // Deserialize the content into the struct
+                <Self as Format>::from_bytes::<T>(&content)
+            }
+            #[doc = concat!("Tries to load a sensitive ", $name, " file into the required struct.")]
+            /// Refuses files accessible by group or others or owned by another user, see [`check_permissions`].
+            pub fn load_sensitive<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+                check_permissions(file_path)?;
+                Self::load::<T>(file_path)
+            }
+            #[cfg(feature = "reqwest")]
+            #[doc = concat!("Tries to fetch a ", $name, " file from the HTTP(S) URL into the required struct.")]
+            /// The response is cached at the path and revalidated with ETag/Last-Modified on the next fetch.
+            /// If the server is unreachable or fails, the cached copy is used instead.
+            pub fn fetch<T: for<'de> Deserialize<'de>>(url: &str, cache_path: &str) -> Result<T, Error> {
+                crate::remote::fetch::<Self, T>(url, cache_path)
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `get`. Tries to load a ", $name, " file from path. If it doesn't find one, it creates one from default.")]
+            pub async fn get_async<T:for<'de> Deserialize<'de> + Serialize + Default>(file_path: &str) -> Result<T, Error> {
+                // Create the config if it does not exist
+                if !tokio::fs::try_exists(file_path).await? {
+                    Self::create_default_async::<T>(file_path).await?;
+                }
+
+                // Try to load the config file
+                Self::load_async::<T>(file_path).await
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `create`. Tries to create a new ", $name, " file from the struct provided.")]
+            pub async fn create_async<T:Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes(content)?;
+
+                // Write the content to the file
+                crate::write_atomic_async(file_path, &parsed, None).await
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `create_with_mode`. Tries to create a new ", $name, " file from the struct provided with the Unix mode (e.g. `0o600`).")]
+            pub async fn create_with_mode_async<T:Serialize>(file_path: &str, content: &T, mode: u32) -> Result<(), Error> {
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes(content)?;
+
+                // Write the content to the file
+                crate::write_atomic_async(file_path, &parsed, Some(mode)).await
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `create_with`. Tries to create a new ", $name, " file from the struct provided, written with the options.")]
+            pub async fn create_with_async<T:Serialize>(file_path: &str, content: &T, options: &WriteOptions) -> Result<(), Error> {
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes_with(content, options)?;
+
+                // Write the content to the file
+                crate::write_atomic_async(file_path, &parsed, None).await
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `create_default`. Tries to create a new ", $name, " file from struct default.")]
+            pub async fn create_default_async<T:Default + Serialize>(file_path: &str) -> Result<(), Error> {
+                Self::create_async(file_path, &T::default()).await
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `save`. Tries to save the struct to an existing ", $name, " file.")]
+            pub async fn save_async<T:Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+                // Make sure the file exists or return with error
+                tokio::fs::metadata(file_path).await?;
+
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes(content)?;
+
+                // Write the content to the file
+                crate::write_atomic_async(file_path, &parsed, None).await
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `save_with`. Tries to save the struct to an existing ", $name, " file, written with the options.")]
+            pub async fn save_with_async<T:Serialize>(file_path: &str, content: &T, options: &WriteOptions) -> Result<(), Error> {
+                // Make sure the file exists or return with error
+                tokio::fs::metadata(file_path).await?;
+
+                // Serialize the struct to the file content
+                let parsed = <Self as Format>::to_bytes_with(content, options)?;
+
+                // Write the content to the file
+                crate::write_atomic_async(file_path, &parsed, None).await
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `load`. Tries to load a ", $name, " file into the required struct.")]
+            pub async fn load_async<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+                // Load the file or return with error
+                let content = tokio::fs::read(file_path).await?;
+
+                // Deserialize the content into the struct
+                <Self as Format>::from_bytes::<T>(&content)
+            }
+            #[cfg(feature = "tokio")]
+            #[doc = concat!("Async version of `load_sensitive`. Tries to load a sensitive ", $name, " file into the required struct.")]
+            /// Refuses files accessible by group or others or owned by another user, see [`check_permissions`].
+            pub async fn load_sensitive_async<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+                crate::check_permissions_async(file_path).await?;
+                Self::load_async::<T>(file_path).await
+            }
+        }
+    };
+}
+pub(crate) use impl_file_api;
 
 // #===========================#
 // #=== TOML IMPLEMENTATION ===#
 
 /// Unit struct holding methods for interacting with TOML files.
 pub struct Toml;
-impl Toml {
-    /// Tries to load a TOML file from path. If it doesn't find one, it creates one from default.
-    pub fn get<T:for<'de> Deserialize<'de> + Serialize + Default>(file_path: &str) -> Result<T, Error> {
-        // Create the config if it does not exist
-        if !fs::exists(file_path)? {
-            Self::create_default::<T>(file_path)?;
-        }
+impl Format for Toml {
+    const EXTENSION: &'static str = "toml";
+    fn to_bytes<T: Serialize + ?Sized>(content: &T) -> Result<Vec<u8>, Error> {
+        Ok(toml::to_string(content)?.into_bytes())
+    }
+    fn from_bytes<T: for<'de> Deserialize<'de>>(content: &[u8]) -> Result<T, Error> {
+        Ok(toml::from_slice::<T>(content)?)
+    }
+    fn to_bytes_with<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<Vec<u8>, Error> {
+        Ok(options.finish(write_options::to_toml(content, options)?))
+    }
+}
+impl_file_api!(Toml, "TOML");
 
-        // Try to load the config file
-        Self::load::<T>(file_path)
+// #===========================#
+// #=== JSON IMPLEMENTATION ===#
+
+/// Unit struct holding methods for interacting with JSON files.
+pub struct Json;
+impl Format for Json {
+    const EXTENSION: &'static str = "json";
+    fn to_bytes<T: Serialize + ?Sized>(content: &T) -> Result<Vec<u8>, Error> {
+        Ok(serde_json::to_vec_pretty(content)?)
+    }
+    fn from_bytes<T: for<'de> Deserialize<'de>>(content: &[u8]) -> Result<T, Error> {
+        Ok(serde_json::from_slice::<T>(content)?)
+    }
+    fn to_bytes_with<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<Vec<u8>, Error> {
+        Ok(options.finish(write_options::to_json(content, options)?))
     }
-    /// Tries to create a new TOML file from the struct provided.
-    pub fn create<T:Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
-        // Create new file or return with error
-        let mut file = fs::File::create(file_path)?;
+}
+impl_file_api!(Json, "JSON");
 
-        // Serialize the struct to TOML string
-        let parsed = toml::to_string(content)?;
+// #==============================#
+// #=== BINCODE IMPLEMENTATION ===#
 
-        // Write the TOML string to the file
-        Ok(file.write_all(parsed.as_bytes())?)
+/// Unit struct holding methods for interacting with bincode files.
+pub struct Bincode;
+impl Format for Bincode {
+    const EXTENSION: &'static str = "bin";
+    fn to_bytes<T: Serialize + ?Sized>(content: &T) -> Result<Vec<u8>, Error> {
+        Ok(bincode::serde::encode_to_vec(content, bincode::config::standard())?)
+    }
+    fn from_bytes<T: for<'de> Deserialize<'de>>(content: &[u8]) -> Result<T, Error> {
+        Ok(bincode::serde::decode_from_slice::<T, _>(content, bincode::config::standard())?.0)
     }
-    /// Tries to create a new TOML file from struct default.
-    pub fn create_default<T:Default + Serialize>(file_path: &str) -> Result<(), Error> {
-        // Create new file or return with error
-        let mut file = fs::File::create(file_path)?;
+}
+impl_file_api!(Bincode, "bincode");
+impl Bincode {
+    /// Tries to create a new bincode file prefixed with [`BINCODE_MAGIC`], so [`detect_file_type`] can recognize it.
+    /// Bincode is binary, so there is no variant taking [`WriteOptions`].
+    pub fn create_with_header<T: Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
+        // Serialize the struct after the header
+        let parsed = [BINCODE_MAGIC.as_slice(), &<Self as Format>::to_bytes(content)?].concat();
 
-        // Serialize the struct to TOML string
-        let parsed = toml::to_string(&T::default())?;
+        // Write the content to the file
+        write_atomic(file_path, &parsed)
+    }
+    /// Tries to load a bincode file written by [`Bincode::create_with_header`] into the required struct.
+    pub fn load_with_header<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+        // Read the file and strip the header
+        let content = std::fs::read(file_path)?;
+        let content = content.strip_prefix(BINCODE_MAGIC.as_slice()).ok_or(Error::BincodeHeader)?;
 
-        // Write the TOML string to the file
-        Ok(file.write_all(parsed.as_bytes())?)
+        // Deserialize the content into the struct
+        <Self as Format>::from_bytes::<T>(content)
     }
-    /// Tries to save the struct to a TOML file.
-    pub fn save<T:Serialize>(file_path: &str, content: &T) -> Result<(), Error> {
-        // Open the file or return with error
-        let mut file = fs::OpenOptions::new().write(true).open(file_path)?;
+}
 
-        // Serialize the struct to TOML string
-        let parsed = toml::to_string(content)?;
+// #===========================#
+// #=== YAML IMPLEMENTATION ===#
 
-        // Write the TOML string to the file
-        Ok(file.write_all(parsed.as_bytes())?)
+/// Unit struct holding methods for interacting with YAML files.
+#[cfg(feature = "yaml")]
+pub struct Yaml;
+#[cfg(feature = "yaml")]
+impl Format for Yaml {
+    const EXTENSION: &'static str = "yaml";
+    fn to_bytes<T: Serialize + ?Sized>(content: &T) -> Result<Vec<u8>, Error> {
+        Ok(serde_norway::to_string(content)?.into_bytes())
+    }
+    fn from_bytes<T: for<'de> Deserialize<'de>>(content: &[u8]) -> Result<T, Error> {
+        Ok(serde_norway::from_slice::<T>(content)?)
+    }
+    /// The YAML serializer has a fixed layout, so only the trailing newline and line ending options apply.
+    fn to_bytes_with<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<Vec<u8>, Error> {
+        Ok(options.finish(serde_norway::to_string(content)?))
     }
-    /// Tries to load a TOML file into the required struct.
-    pub fn load<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
-        // Load the file to string or return with error
-        let content = fs::read_to_string(file_path)?;
+}
+#[cfg(feature = "yaml")]
+impl_file_api!(Yaml, "YAML");
+
+// #==========================#
+// #=== RON IMPLEMENTATION ===#
 
-        // Deserialize the toml config into the struct
-        Ok(toml::from_str::<T>(&content)?)
+/// Unit struct holding methods for interacting with RON files.
+#[cfg(feature = "ron")]
+pub struct Ron;
+#[cfg(feature = "ron")]
+impl Format for Ron {
+    const EXTENSION: &'static str = "ron";
+    fn to_bytes<T: Serialize + ?Sized>(content: &T) -> Result<Vec<u8>, Error> {
+        Ok(ron::ser::to_string_pretty(content, ron::ser::PrettyConfig::default())?.into_bytes())
+    }
+    fn from_bytes<T: for<'de> Deserialize<'de>>(content: &[u8]) -> Result<T, Error> {
+        Ok(ron::de::from_bytes::<T>(content)?)
+    }
+    fn to_bytes_with<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<Vec<u8>, Error> {
+        Ok(options.finish(write_options::to_ron(content, options)?))
     }
 }
+#[cfg(feature = "ron")]
+impl_file_api!(Ron, "RON");
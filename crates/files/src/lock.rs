@@ -0,0 +1,149 @@
+use std::{fs, io::Write, path::{Path, PathBuf}};
+
+use crate::Error;
+
+#[test]
+fn test() {
+    let path = std::env::temp_dir().join(format!("util_files_lock_{}.pid", std::process::id()));
+
+    let lock = LockFile::acquire(&path).unwrap();
+    assert!(matches!(LockFile::acquire(&path), Err(Error::Locked { pid, .. }) if pid == std::process::id()));
+    drop(lock);
+    assert!(!path.exists());
+
+    // Locks of dead processes are replaced
+    #[cfg(unix)]
+    {
+        fs::write(&path, "4294967294").unwrap();
+        let lock = LockFile::acquire(&path).unwrap();
+        assert_eq!(fs::read_to_string(lock.path()).unwrap(), std::process::id().to_string());
+        drop(lock);
+
+        // Racing threads never hold the lock at the same time
+        let held = std::sync::atomic::AtomicUsize::new(0);
+        std::thread::scope(|scope| for _ in 0..8 {
+            scope.spawn(|| for _ in 0..50 {
+                if let Ok(_lock) = LockFile::acquire(&path) {
+                    assert_eq!(held.fetch_add(1, std::sync::atomic::Ordering::SeqCst), 0);
+                    held.fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
+                }
+            });
+        });
+        assert!(!path.exists());
+    }
+}
+
+// #=================#
+// #=== LOCK FILE ===#
+
+/// Single-instance lock backed by a PID file. The lock is held until the guard is dropped.
+///
+/// On Unix the lock is an exclusive `flock` on the PID file, which the kernel releases when the holding process dies.
+/// A lock left behind by a dead process is therefore taken over atomically, without racing other processes.
+/// Elsewhere the lock is the existence of the PID file and a stale lock must be removed by hand.
+#[derive(Debug)]
+pub struct LockFile {
+    path: PathBuf,
+    /// Open PID file holding the `flock`, closing it releases the lock
+    #[cfg(unix)]
+    _file: fs::File,
+}
+impl LockFile {
+    /// Tries to acquire the lock, writing the PID of this process into the file.
+    /// Fails with [`Error::Locked`] reporting the owning PID if another live process holds the lock.
+    #[cfg(unix)]
+    pub fn acquire(file_path: impl AsRef<Path>) -> Result<Self, Error> {
+        use std::os::{fd::AsRawFd, unix::fs::MetadataExt};
+
+        let path = file_path.as_ref().to_path_buf();
+        loop {
+            let mut file = fs::OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&path)?;
+
+            // SAFETY: the descriptor stays open for the duration of the call
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+                let error = std::io::Error::last_os_error();
+                if error.raw_os_error() != Some(libc::EWOULDBLOCK) {
+                    return Err(error.into());
+                }
+                match Self::owner(&path) {
+                    Ok(pid) => return Err(Error::Locked { path: path.to_string_lossy().into_owned(), pid }),
+                    // The holder has not written its PID yet or just released the lock
+                    Err(_) => { std::thread::yield_now(); continue },
+                }
+            }
+
+            // The previous holder may have removed the file after it was opened, lock the new one instead
+            let current = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(error.into()),
+            };
+            let locked = file.metadata()?;
+            if (locked.dev(), locked.ino()) != (current.dev(), current.ino()) {
+                continue;
+            }
+
+            // Replace the PID of a dead holder with ours
+            file.set_len(0)?;
+            file.write_all(std::process::id().to_string().as_bytes())?;
+            file.sync_all()?;
+            return Ok(LockFile { path, _file: file });
+        }
+    }
+    /// Tries to acquire the lock by creating the PID file exclusively.
+    /// Fails with [`Error::Locked`] reporting the owning PID if the file already exists.
+    #[cfg(not(unix))]
+    pub fn acquire(file_path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = file_path.as_ref().to_path_buf();
+        loop {
+            match Self::create(&path) {
+                Ok(()) => return Ok(LockFile { path }),
+                Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => {},
+                Err(error) => return Err(error.into()),
+            }
+            match Self::owner(&path) {
+                Ok(pid) => return Err(Error::Locked { path: path.to_string_lossy().into_owned(), pid }),
+                // The lock was released in the meantime
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(error) => return Err(error.into()),
+            }
+        }
+    }
+    /// Returns the PID of the process holding the lock at the path.
+    pub fn owner(file_path: impl AsRef<Path>) -> std::io::Result<u32> {
+        let content = fs::read_to_string(file_path)?;
+        content.trim().parse().map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "lock file does not contain a PID"))
+    }
+    /// Returns the path of the PID file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// Creates the PID file with its content in one step, so no one ever sees an empty lock.
+    #[cfg(not(unix))]
+    fn create(path: &Path) -> std::io::Result<()> {
+        let temp_path = crate::temp_sibling(path);
+        let result = (|| {
+            let mut file = fs::File::create_new(&temp_path)?;
+            file.write_all(std::process::id().to_string().as_bytes())?;
+            file.sync_all()?;
+
+            // Linking fails if the lock already exists
+            fs::hard_link(&temp_path, path)
+        })();
+        let _ = fs::remove_file(&temp_path);
+        result
+    }
+}
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        // On Unix the file is still locked by us, so it is removed before anyone else can take it over
+        #[cfg(unix)]
+        let _ = fs::remove_file(&self.path);
+
+        // Elsewhere only remove the lock if it is still ours
+        #[cfg(not(unix))]
+        if Self::owner(&self.path).is_ok_and(|pid| pid == std::process::id()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::{BTreeMap, BTreeSet}, fs, io::Read, path::Path};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{Error, walk_files};
+
+#[test]
+fn test() {
+    use crate::Toml;
+
+    let dir = std::env::temp_dir().join(format!("util_files_manifest_{}", std::process::id()));
+    fs::create_dir_all(dir.join("assets")).unwrap();
+    fs::write(dir.join("assets/a.txt"), "a").unwrap();
+    fs::write(dir.join("b.txt"), "b").unwrap();
+
+    // The manifest round-trips through the TOML writer
+    let manifest_path = std::env::temp_dir().join(format!("util_files_manifest_{}.toml", std::process::id()));
+    let manifest_path = manifest_path.to_str().unwrap();
+    Toml::create(manifest_path, &Manifest::compute(&dir).unwrap()).unwrap();
+    let manifest = Toml::load::<Manifest>(manifest_path).unwrap();
+    assert!(manifest.verify(&dir).unwrap().is_ok());
+
+    fs::write(dir.join("assets/a.txt"), "c").unwrap();
+    fs::write(dir.join("c.txt"), "c").unwrap();
+    fs::remove_file(dir.join("b.txt")).unwrap();
+    let report = manifest.verify(&dir).unwrap();
+    assert_eq!(report, ManifestReport { missing: vec!["b.txt".into()], extra: vec!["c.txt".into()], modified: vec!["assets/a.txt".into()] });
+
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_file(manifest_path).unwrap();
+}
+
+// #===============#
+// #=== HASHING ===#
+
+/// Computes the SHA-256 of the file as a lowercase hex string.
+pub(crate) fn hash_file(file_path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(file_path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(to_hex(&hasher.finalize()))
+}
+
+/// Formats the bytes as a lowercase hex string.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Formats the relative path with `/` separators on every platform.
+pub(crate) fn portable_path(relative: &Path) -> String {
+    relative.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+// #================#
+// #=== MANIFEST ===#
+
+/// Size and hash of a single file in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Size of the file in bytes
+    pub size: u64,
+    /// SHA-256 of the file as a lowercase hex string
+    pub sha256: String,
+}
+
+/// Integrity manifest of a directory tree, listing every file by its relative path.
+/// Serialize it with any of the writers, e.g. `Toml::create("manifest.toml", &manifest)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Files keyed by their path relative to the directory, using `/` separators
+    pub files: BTreeMap<String, ManifestEntry>,
+}
+impl Manifest {
+    /// Computes the manifest of all regular files under the directory. Symbolic links are skipped.
+    pub fn compute(dir: impl AsRef<Path>) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+        let mut files = BTreeMap::new();
+        for relative in walk_files(dir)? {
+            let path = dir.join(&relative);
+            let entry = ManifestEntry { size: fs::metadata(&path)?.len(), sha256: hash_file(&path)? };
+            files.insert(portable_path(&relative), entry);
+        }
+        Ok(Manifest { files })
+    }
+    /// Verifies the directory against the manifest, reporting missing, extra and modified files.
+    pub fn verify(&self, dir: impl AsRef<Path>) -> Result<ManifestReport, Error> {
+        let dir = dir.as_ref();
+        let mut report = ManifestReport::default();
+
+        // Compare every file present in the directory
+        let mut present = BTreeSet::new();
+        for relative in walk_files(dir)? {
+            let path = dir.join(&relative);
+            let name = portable_path(&relative);
+            match self.files.get(&name) {
+                None => report.extra.push(name.clone()),
+                // Hash only files of the expected size
+                Some(entry) => if fs::metadata(&path)?.len() != entry.size || hash_file(&path)? != entry.sha256 {
+                    report.modified.push(name.clone());
+                },
+            }
+            present.insert(name);
+        }
+
+        // Report files missing from the directory
+        report.missing = self.files.keys().filter(|name| !present.contains(*name)).cloned().collect();
+        Ok(report)
+    }
+}
+
+/// Differences found when verifying a directory against a manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ManifestReport {
+    /// Files listed in the manifest but missing from the directory
+    pub missing: Vec<String>,
+    /// Files present in the directory but not listed in the manifest
+    pub extra: Vec<String>,
+    /// Files whose size or hash differ from the manifest
+    pub modified: Vec<String>,
+}
+impl ManifestReport {
+    /// Returns true if the directory matches the manifest exactly.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.extra.is_empty() && self.modified.is_empty()
+    }
+}
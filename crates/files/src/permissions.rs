@@ -0,0 +1,64 @@
+use crate::Error;
+
+#[cfg(unix)]
+#[test]
+fn test() {
+    use crate::Toml;
+
+    let path = std::env::temp_dir().join(format!("util_files_permissions_{}.toml", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    Toml::create_with_mode(path, &toml::Table::new(), 0o600).unwrap();
+    Toml::load_sensitive::<toml::Table>(path).unwrap();
+
+    // Atomic saves keep the permissions of the file
+    Toml::save(path, &toml::Table::new()).unwrap();
+    check_permissions(path).unwrap();
+
+    Toml::create_with_mode(path, &toml::Table::new(), 0o644).unwrap();
+    assert!(matches!(Toml::load_sensitive::<toml::Table>(path), Err(Error::InsecurePermissions { mode: 0o644, .. })));
+    std::fs::remove_file(path).unwrap();
+}
+
+/// Checks that a sensitive file is owned by the current user and not accessible by group or others.
+/// Use this directly to only warn about insecure files instead of refusing to load them.
+/// Always succeeds on platforms without Unix permissions.
+#[cfg(unix)]
+pub fn check_permissions(file_path: &str) -> Result<(), Error> {
+    check_metadata(file_path, &std::fs::metadata(file_path)?)
+}
+
+/// Checks the metadata of a sensitive file, see [`check_permissions`].
+#[cfg(unix)]
+pub(crate) fn check_metadata(file_path: &str, metadata: &std::fs::Metadata) -> Result<(), Error> {
+    use std::os::unix::fs::MetadataExt;
+
+    // Check the file is not accessible by group or others
+    let mode = metadata.mode() & 0o777;
+    if mode & 0o077 != 0 {
+        return Err(Error::InsecurePermissions { path: file_path.to_string(), mode });
+    }
+
+    // Check the file is owned by the current user
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let user = unsafe { libc::geteuid() };
+    if metadata.uid() != user {
+        return Err(Error::InsecureOwner { path: file_path.to_string(), owner: metadata.uid() });
+    }
+    Ok(())
+}
+
+/// Checks that a sensitive file is owned by the current user and not accessible by group or others.
+/// Use this directly to only warn about insecure files instead of refusing to load them.
+/// Always succeeds on platforms without Unix permissions.
+#[cfg(not(unix))]
+pub fn check_permissions(file_path: &str) -> Result<(), Error> {
+    std::fs::metadata(file_path)?;
+    Ok(())
+}
+
+/// Checks the metadata of a sensitive file, see [`check_permissions`].
+#[cfg(not(unix))]
+pub(crate) fn check_metadata(_file_path: &str, _metadata: &std::fs::Metadata) -> Result<(), Error> {
+    Ok(())
+}
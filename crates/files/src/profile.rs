@@ -0,0 +1,72 @@
+use std::env;
+use serde::Deserialize;
+
+use crate::{Error, Format, Json, Toml, document::{Document, read_document}};
+
+#[test]
+fn test() {
+    #[derive(Deserialize)]
+    struct Config { port: u16, host: String }
+
+    let path = env::temp_dir().join(format!("util_files_profile_{}.toml", std::process::id()));
+    let path = path.to_str().unwrap();
+    std::fs::write(path, "[default]\nport = 80\nhost = \"localhost\"\n\n[prod]\nhost = \"example.com\"\n").unwrap();
+
+    let config = Toml::load_profile::<Config>(path, "prod").unwrap();
+    assert_eq!((config.port, config.host.as_str()), (80, "example.com"));
+    assert!(matches!(Toml::load_profile::<Config>(path, "dev"), Err(Error::UnknownProfile { .. })));
+    std::fs::remove_file(path).unwrap();
+}
+
+/// Name of the section every profile is merged over.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Loads the default section with the profile section deep-merged over it.
+fn load_profile<F: Format, D: Document, T: for<'de> Deserialize<'de>>(file_path: &str, profile: &str) -> Result<T, Error> {
+    let mut document = read_document::<F, D>(file_path)?;
+
+    // Collect the available profiles
+    let available: Vec<String> = document.keys().into_iter()
+        .filter(|key| document.child(&key.as_str().into()).is_some_and(Document::is_table))
+        .collect();
+    if profile != DEFAULT_PROFILE && !available.iter().any(|name| name == profile) {
+        return Err(Error::UnknownProfile { profile: profile.to_string(), available });
+    }
+
+    // Merge the profile over the default section
+    let mut merged = document.remove_child(&DEFAULT_PROFILE.into()).unwrap_or_else(D::empty_table);
+    if profile != DEFAULT_PROFILE && let Some(section) = document.remove_child(&profile.into()) {
+        merged.merge(section);
+    }
+    merged.into_struct::<T>()
+}
+
+/// Loads the profile named by the environment variable, or the default profile if it is not set.
+fn load_profile_env<F: Format, D: Document, T: for<'de> Deserialize<'de>>(file_path: &str, env_var: &str) -> Result<T, Error> {
+    let profile = env::var(env_var).ok().filter(|profile| !profile.is_empty());
+    load_profile::<F, D, T>(file_path, profile.as_deref().unwrap_or(DEFAULT_PROFILE))
+}
+
+impl Toml {
+    /// Tries to load the profile section of a TOML file deep-merged over its `[default]` section.
+    pub fn load_profile<T: for<'de> Deserialize<'de>>(file_path: &str, profile: &str) -> Result<T, Error> {
+        load_profile::<Self, toml::Value, T>(file_path, profile)
+    }
+    /// Tries to load the profile named by the environment variable from a TOML file.
+    /// Falls back to the `[default]` section if the variable is not set.
+    pub fn load_profile_env<T: for<'de> Deserialize<'de>>(file_path: &str, env_var: &str) -> Result<T, Error> {
+        load_profile_env::<Self, toml::Value, T>(file_path, env_var)
+    }
+}
+
+impl Json {
+    /// Tries to load the profile object of a JSON file deep-merged over its `"default"` object.
+    pub fn load_profile<T: for<'de> Deserialize<'de>>(file_path: &str, profile: &str) -> Result<T, Error> {
+        load_profile::<Self, serde_json::Value, T>(file_path, profile)
+    }
+    /// Tries to load the profile named by the environment variable from a JSON file.
+    /// Falls back to the `"default"` object if the variable is not set.
+    pub fn load_profile_env<T: for<'de> Deserialize<'de>>(file_path: &str, env_var: &str) -> Result<T, Error> {
+        load_profile_env::<Self, serde_json::Value, T>(file_path, env_var)
+    }
+}
@@ -0,0 +1,116 @@
+use std::{fs, time::Duration};
+use reqwest::{StatusCode, blocking::{Client, Response}, header::{ETAG, HeaderName, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED}};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Format, Json, write_atomic};
+
+#[test]
+fn test() {
+    use std::{io::{BufRead, BufReader, Write}, net::TcpListener};
+    use crate::Toml;
+
+    // Local stand-in serving the file once, then answering revalidation with 304
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/config.toml", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let mut revalidated = false;
+        for index in 0..2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            while reader.read_line(&mut request).unwrap() > 2 {}
+            revalidated |= request.to_lowercase().contains("if-none-match: \"v1\"");
+            let response = match index {
+                0 => "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 9\r\nConnection: close\r\n\r\nport = 80",
+                _ => "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n",
+            };
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+        revalidated
+    });
+
+    let cache = std::env::temp_dir().join(format!("util_files_remote_{}.toml", std::process::id()));
+    let cache = cache.to_str().unwrap();
+    assert_eq!(Toml::fetch::<toml::Table>(&url, cache).unwrap()["port"].as_integer(), Some(80));
+    assert_eq!(Toml::fetch::<toml::Table>(&url, cache).unwrap()["port"].as_integer(), Some(80));
+    assert!(server.join().unwrap());
+
+    // The server is gone, the cached copy is used
+    assert_eq!(Toml::fetch::<toml::Table>(&url, cache).unwrap()["port"].as_integer(), Some(80));
+    fs::remove_file(cache).unwrap();
+    fs::remove_file(format!("{cache}.meta")).unwrap();
+
+    // A 304 without a cached copy is reported instead of parsing the empty body
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/config.toml", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        while reader.read_line(&mut String::new()).unwrap() > 2 {}
+        stream.write_all(b"HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n").unwrap();
+    });
+    assert!(matches!(Toml::fetch::<toml::Table>(&url, cache), Err(Error::NotModified(_))));
+    server.join().unwrap();
+}
+
+/// Timeout of a single fetch.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Validators of the cached response, stored next to the cached file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheMeta {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// Returns the header value as a string.
+fn header(response: &Response, name: HeaderName) -> Option<String> {
+    response.headers().get(name)?.to_str().ok().map(str::to_string)
+}
+
+/// Fetches the file from the URL, revalidating and falling back to the cached copy.
+pub(crate) fn fetch<F: Format, T: for<'de> Deserialize<'de>>(url: &str, cache_path: &str) -> Result<T, Error> {
+    let meta_path = format!("{cache_path}.meta");
+
+    // Use the cache only if it was fetched from the same URL
+    let meta = Json::load::<CacheMeta>(&meta_path).ok().filter(|meta| meta.url == url);
+    let cached = meta.as_ref().and_then(|_| fs::read(cache_path).ok());
+
+    // Send the request with the validators of the cached copy
+    let client = Client::builder().timeout(FETCH_TIMEOUT).build()?;
+    let mut request = client.get(url);
+    if let Some(meta) = meta.as_ref().filter(|_| cached.is_some()) {
+        if let Some(etag) = &meta.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &meta.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = match request.send().and_then(|response| response.error_for_status()) {
+        Ok(response) => response,
+        // Fall back to the cached copy if the server is unreachable or fails
+        Err(error) => return match cached {
+            Some(cached) => F::from_bytes::<T>(&cached),
+            None => Err(error.into()),
+        },
+    };
+    if response.status() == StatusCode::NOT_MODIFIED {
+        // No validators are sent without a cached copy, so the server is misbehaving
+        return match cached {
+            Some(cached) => F::from_bytes::<T>(&cached),
+            None => Err(Error::NotModified(url.to_string())),
+        };
+    }
+
+    // Parse the new content before replacing the cached copy
+    let meta = CacheMeta { url: url.to_string(), etag: header(&response, ETAG), last_modified: header(&response, LAST_MODIFIED) };
+    let body = response.bytes()?;
+    let content = F::from_bytes::<T>(&body)?;
+
+    write_atomic(cache_path, &body)?;
+    write_atomic(&meta_path, &Json::to_bytes(&meta)?)?;
+    Ok(content)
+}
@@ -0,0 +1,70 @@
+use std::fmt::{Debug, Display};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::Zeroize;
+
+#[test]
+fn test() {
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Login { user: String, token: Secret<String> }
+
+    // The value never shows up when printed
+    let login = Login { user: "admin".into(), token: Secret::new("hunter2".to_string()) };
+    assert_eq!(format!("{login:?}"), "Login { user: \"admin\", token: [REDACTED] }");
+    assert_eq!(login.token.to_string(), "[REDACTED]");
+    assert_eq!(login.token.expose(), "hunter2");
+
+    // It (de)serializes as the plain inner value
+    let json = serde_json::to_string(&login).unwrap();
+    assert_eq!(json, "{\"user\":\"admin\",\"token\":\"hunter2\"}");
+    assert_eq!(serde_json::from_str::<Login>(&json).unwrap().token.expose(), "hunter2");
+    assert_eq!(Secret::from(vec![1u8, 2]).expose(), &[1, 2]);
+}
+
+// #====================#
+// #=== SECRET VALUE ===#
+
+/// Value that never shows up in logs. It (de)serializes as the inner value,
+/// prints as `[REDACTED]` in `Debug` and `Display` and is zeroized in memory on drop.
+/// The value can only be read with [`Secret::expose`].
+#[derive(Clone, Default)]
+pub struct Secret<T: Zeroize>(T);
+impl <T: Zeroize> Secret<T> {
+    /// Wraps the value.
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+    /// Returns the secret value. Keep the exposed reference out of logs.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+}
+impl <T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+impl <T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+impl <T: Zeroize> Debug for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+impl <T: Zeroize> Display for Secret<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[REDACTED]")
+    }
+}
+impl <T: Zeroize + Serialize> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+impl <'de, T: Zeroize + Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Secret)
+    }
+}
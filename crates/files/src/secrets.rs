@@ -0,0 +1,278 @@
+use std::{fmt::Debug, fs, io::Write};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce, aead::{Aead, Generate, Key, KeyInit}};
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{Error, Format, Json, Toml, WriteOptions, write_atomic, document::{Document, Segment, document_bytes, format_key_path, parse_key_path, read_document, walk}};
+
+#[test]
+fn test() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Config { user: String, token: String }
+
+    let path = std::env::temp_dir().join(format!("util_files_secrets_{}.toml", std::process::id()));
+    let path = path.to_str().unwrap();
+    let key = SecretKey::from_passphrase("hunter2", "util_files").unwrap();
+    let config = Config { user: "admin".into(), token: "secret".into() };
+
+    Toml::create_encrypted(path, &config, &key, &["token"]).unwrap();
+    assert!(!fs::read_to_string(path).unwrap().contains("secret"));
+    assert_eq!(Toml::load_encrypted::<Config>(path, &key).unwrap(), config);
+
+    // Values encrypted in the file stay encrypted on save
+    Toml::save_encrypted(path, &Config { user: "root".into(), token: "other".into() }, &key, &[]).unwrap();
+    assert_eq!(Toml::get_key(path, "user").unwrap().as_str(), Some("root"));
+    assert!(Toml::get_key(path, "token").unwrap().as_str().unwrap().starts_with(ENCRYPTED_PREFIX));
+
+    // Secret values load transparently but never show up in logs
+    #[derive(Deserialize, Debug)]
+    struct Login { user: String, token: crate::Secret<String> }
+    let login = Toml::load_encrypted::<Login>(path, &key).unwrap();
+    assert_eq!(login.token.expose(), "other");
+    assert!(!format!("{login:?} {}", login.token).contains("other"));
+    assert_eq!(login.user, "root");
+    fs::remove_file(path).unwrap();
+
+    // Encrypted files can be written with options
+    let path = path.replace(".toml", ".json");
+    Json::create_encrypted_with(&path, &config, &key, &["token"], &WriteOptions::new().compact()).unwrap();
+    assert!(fs::read_to_string(&path).unwrap().starts_with("{\"token\":\"enc:"));
+    assert_eq!(Json::load_encrypted::<Config>(&path, &key).unwrap(), config);
+    fs::remove_file(&path).unwrap();
+
+    // Keys are only copied explicitly and wiped on drop
+    let mut copy = key.duplicate();
+    assert_eq!(copy.0, key.0);
+    copy.zeroize();
+    assert_eq!(copy.0, [0; 32]);
+}
+
+/// Prefix marking an encrypted string value in a file.
+pub const ENCRYPTED_PREFIX: &str = "enc:";
+
+/// Length of the nonce stored in front of the ciphertext.
+const NONCE_LEN: usize = 12;
+
+// #==================#
+// #=== SECRET KEY ===#
+
+/// Key used to encrypt and decrypt secret values stored in files.
+/// The key bytes are zeroized on drop and only copied with [`SecretKey::duplicate`].
+pub struct SecretKey([u8; 32]);
+impl SecretKey {
+    /// Generates a new random key.
+    pub fn generate() -> Self {
+        SecretKey(Key::<ChaCha20Poly1305>::generate().into())
+    }
+    /// Derives the key from a passphrase using Argon2. The same passphrase and salt always yield the same key.
+    pub fn from_passphrase(passphrase: &str, salt: &str) -> Result<Self, Error> {
+        let mut key = SecretKey([0u8; 32]);
+        argon2::Argon2::default().hash_password_into(passphrase.as_bytes(), salt.as_bytes(), &mut key.0).map_err(|error| Error::Encryption(error.to_string()))?;
+        Ok(key)
+    }
+    /// Returns a copy of the key, which is zeroized on drop as well.
+    pub fn duplicate(&self) -> Self {
+        SecretKey(self.0)
+    }
+    /// Tries to load the key from a key file. If it doesn't find one, it generates and saves a new key.
+    pub fn get(file_path: &str) -> Result<Self, Error> {
+        // Create the key file if it does not exist
+        if !fs::exists(file_path)? {
+            return Self::create(file_path);
+        }
+
+        // Try to load the key file
+        Self::load(file_path)
+    }
+    /// Tries to generate a new key and save it to a key file readable only by the owner.
+    /// Fails if the file already exists, so an existing key is never overwritten.
+    pub fn create(file_path: &str) -> Result<Self, Error> {
+        let key = Self::generate();
+
+        // Create the key file with restricted permissions
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(file_path)?;
+
+        // Write the encoded key to the file
+        file.write_all(STANDARD.encode(key.0).as_bytes())?;
+        Ok(key)
+    }
+    /// Tries to load the key from a key file.
+    pub fn load(file_path: &str) -> Result<Self, Error> {
+        let mut encoded = fs::read_to_string(file_path)?;
+        let decoded = STANDARD.decode(encoded.trim());
+        encoded.zeroize();
+        let mut decoded = decoded.map_err(|error| Error::Encryption(error.to_string()))?;
+
+        // Copy the bytes into the key and wipe the buffer
+        let mut key = SecretKey([0u8; 32]);
+        let result = match decoded.len() == key.0.len() {
+            true => { key.0.copy_from_slice(&decoded); Ok(key) },
+            false => Err(Error::Encryption("key file does not contain a 256-bit key".to_string())),
+        };
+        decoded.zeroize();
+        result
+    }
+    /// Encrypts the value into the `enc:` form, ready to be pasted into a file.
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, Error> {
+        let cipher = ChaCha20Poly1305::new(&self.0.into());
+        let nonce = Nonce::generate();
+        let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes()).map_err(|error| Error::Encryption(error.to_string()))?;
+
+        // Store the nonce in front of the ciphertext
+        let mut payload = nonce.to_vec();
+        payload.extend(ciphertext);
+        Ok(format!("{ENCRYPTED_PREFIX}{}", STANDARD.encode(payload)))
+    }
+    /// Decrypts a value in the `enc:` form.
+    pub fn decrypt(&self, value: &str) -> Result<String, Error> {
+        let encoded = value.strip_prefix(ENCRYPTED_PREFIX).ok_or_else(|| Error::Encryption(format!("value is missing the `{ENCRYPTED_PREFIX}` prefix")))?;
+        let payload = STANDARD.decode(encoded).map_err(|error| Error::Encryption(error.to_string()))?;
+        if payload.len() < NONCE_LEN {
+            return Err(Error::Encryption("value is too short".to_string()));
+        }
+
+        // Split the nonce from the ciphertext
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let nonce = Nonce::try_from(nonce).map_err(|_| Error::Encryption("invalid nonce".to_string()))?;
+        let cipher = ChaCha20Poly1305::new(&self.0.into());
+        let plaintext = cipher.decrypt(&nonce, ciphertext).map_err(|_| Error::Encryption("wrong key or corrupted value".to_string()))?;
+        String::from_utf8(plaintext).map_err(|error| Error::Encryption(error.to_string()))
+    }
+}
+impl Zeroize for SecretKey {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+impl ZeroizeOnDrop for SecretKey {}
+impl Debug for SecretKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+// #===========================#
+// #=== ENCRYPTED DOCUMENTS ===#
+
+/// Collects the paths and values of all encrypted strings in the document.
+fn collect_encrypted<D: Document>(node: &D, path: &mut Vec<Segment>, found: &mut Vec<(Vec<Segment>, String)>) {
+    if let Some(value) = node.as_string() {
+        if value.starts_with(ENCRYPTED_PREFIX) {
+            found.push((path.clone(), value.to_string()));
+        }
+        return;
+    }
+    for (segment, child) in node.children() {
+        path.push(segment);
+        collect_encrypted(child, path, found);
+        path.pop();
+    }
+}
+
+/// Loads the document, decrypting all encrypted strings before deserialization.
+fn load_encrypted<F: Format, D: Document, T: for<'de> Deserialize<'de>>(file_path: &str, key: &SecretKey) -> Result<T, Error> {
+    let mut document = read_document::<F, D>(file_path)?;
+
+    let mut encrypted = Vec::new();
+    collect_encrypted(&document, &mut Vec::new(), &mut encrypted);
+    for (path, value) in encrypted {
+        *walk(&mut document, &path, &format_key_path(&path))? = D::from_string(key.decrypt(&value)?);
+    }
+    document.into_struct::<T>()
+}
+
+/// Saves the struct, encrypting the listed paths and all paths already encrypted in the existing file.
+fn write_encrypted<F: Format, D: Document, T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str], options: Option<&WriteOptions>) -> Result<(), Error> {
+    let mut document = D::from_struct(content)?;
+
+    // Collect the paths encrypted in the existing file
+    let mut encrypted = Vec::new();
+    if fs::exists(file_path)? {
+        collect_encrypted(&read_document::<F, D>(file_path)?, &mut Vec::new(), &mut encrypted);
+    }
+    for secret_path in secret_paths {
+        let path = parse_key_path(secret_path)?;
+        if !encrypted.iter().any(|(existing, _)| *existing == path) {
+            encrypted.push((path, String::new()));
+        }
+    }
+
+    for (path, previous) in encrypted {
+        let key_path = format_key_path(&path);
+        let Ok(node) = walk(&mut document, &path, &key_path) else { continue };
+        let plaintext = node.as_string().ok_or_else(|| Error::Encryption(format!("the value at `{key_path}` is not a string")))?;
+
+        // Keep the previous ciphertext if the value did not change, to avoid needless diffs
+        let value = match key.decrypt(&previous) {
+            Ok(decrypted) if decrypted == plaintext => previous,
+            _ => key.encrypt(plaintext)?,
+        };
+        *node = D::from_string(value);
+    }
+
+    write_atomic(file_path, &document_bytes::<F, D>(&document, options)?)
+}
+
+impl Toml {
+    /// Tries to load a TOML file into the required struct, decrypting all `enc:` values.
+    pub fn load_encrypted<T: for<'de> Deserialize<'de>>(file_path: &str, key: &SecretKey) -> Result<T, Error> {
+        load_encrypted::<Self, toml::Value, T>(file_path, key)
+    }
+    /// Tries to create a new TOML file from the struct provided, encrypting the values at the listed key paths.
+    pub fn create_encrypted<T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str]) -> Result<(), Error> {
+        write_encrypted::<Self, toml::Value, T>(file_path, content, key, secret_paths, None)
+    }
+    /// Tries to create a new TOML file from the struct provided written with the options, encrypting the values at the listed key paths.
+    pub fn create_encrypted_with<T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str], options: &WriteOptions) -> Result<(), Error> {
+        write_encrypted::<Self, toml::Value, T>(file_path, content, key, secret_paths, Some(options))
+    }
+    /// Tries to save the struct to an existing TOML file.
+    /// Values encrypted in the file stay encrypted, values at the listed key paths are encrypted too.
+    pub fn save_encrypted<T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str]) -> Result<(), Error> {
+        fs::metadata(file_path)?;
+        write_encrypted::<Self, toml::Value, T>(file_path, content, key, secret_paths, None)
+    }
+    /// Tries to save the struct written with the options to an existing TOML file.
+    /// Values encrypted in the file stay encrypted, values at the listed key paths are encrypted too.
+    pub fn save_encrypted_with<T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str], options: &WriteOptions) -> Result<(), Error> {
+        fs::metadata(file_path)?;
+        write_encrypted::<Self, toml::Value, T>(file_path, content, key, secret_paths, Some(options))
+    }
+}
+
+impl Json {
+    /// Tries to load a JSON file into the required struct, decrypting all `enc:` values.
+    pub fn load_encrypted<T: for<'de> Deserialize<'de>>(file_path: &str, key: &SecretKey) -> Result<T, Error> {
+        load_encrypted::<Self, serde_json::Value, T>(file_path, key)
+    }
+    /// Tries to create a new JSON file from the struct provided, encrypting the values at the listed key paths.
+    pub fn create_encrypted<T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str]) -> Result<(), Error> {
+        write_encrypted::<Self, serde_json::Value, T>(file_path, content, key, secret_paths, None)
+    }
+    /// Tries to create a new JSON file from the struct provided written with the options, encrypting the values at the listed key paths.
+    pub fn create_encrypted_with<T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str], options: &WriteOptions) -> Result<(), Error> {
+        write_encrypted::<Self, serde_json::Value, T>(file_path, content, key, secret_paths, Some(options))
+    }
+    /// Tries to save the struct to an existing JSON file.
+    /// Values encrypted in the file stay encrypted, values at the listed key paths are encrypted too.
+    pub fn save_encrypted<T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str]) -> Result<(), Error> {
+        fs::metadata(file_path)?;
+        write_encrypted::<Self, serde_json::Value, T>(file_path, content, key, secret_paths, None)
+    }
+    /// Tries to save the struct written with the options to an existing JSON file.
+    /// Values encrypted in the file stay encrypted, values at the listed key paths are encrypted too.
+    pub fn save_encrypted_with<T: Serialize>(file_path: &str, content: &T, key: &SecretKey, secret_paths: &[&str], options: &WriteOptions) -> Result<(), Error> {
+        fs::metadata(file_path)?;
+        write_encrypted::<Self, serde_json::Value, T>(file_path, content, key, secret_paths, Some(options))
+    }
+}
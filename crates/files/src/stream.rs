@@ -0,0 +1,181 @@
+use std::{fs, io::{BufRead, BufReader}, marker::PhantomData};
+use serde::Deserialize;
+
+use crate::{Bincode, Error, Json};
+
+#[test]
+fn test() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Row { id: u32, name: String }
+
+    let path = std::env::temp_dir().join(format!("util_files_stream_{}.json", std::process::id()));
+    let path = path.to_str().unwrap();
+    fs::write(path, " [ {\"id\": 1, \"name\": \"a]\\\"\"}, {\"id\": 2, \"name\": \"b\"} ] ").unwrap();
+    let rows: Vec<Row> = Json::iter_array::<Row>(path).unwrap().collect::<Result<_, _>>().unwrap();
+    assert_eq!(rows, vec![Row { id: 1, name: "a]\"".into() }, Row { id: 2, name: "b".into() }]);
+
+    // Scalars and malformed arrays
+    let numbers: Vec<i64> = JsonArrayIter::new("[1, -2,3]".as_bytes()).collect::<Result<_, _>>().unwrap();
+    assert_eq!(numbers, vec![1, -2, 3]);
+    assert!(JsonArrayIter::<i64, _>::new("[1 2]".as_bytes()).any(|item| item.is_err()));
+
+    Bincode::create(path, &vec![1u64, 2, 3]).unwrap();
+    assert_eq!(Bincode::load_streaming::<Vec<u64>>(path).unwrap(), vec![1, 2, 3]);
+    fs::remove_file(path).unwrap();
+}
+
+// #=======================#
+// #=== STREAMING LOADS ===#
+
+impl Json {
+    /// Tries to load a JSON file into the required struct, reading it through a buffer instead of loading it whole into memory.
+    pub fn load_streaming<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+        let reader = BufReader::new(fs::File::open(file_path)?);
+        Ok(serde_json::from_reader::<_, T>(reader)?)
+    }
+    /// Tries to open a JSON file containing a top-level array, iterating over its elements one at a time.
+    /// Only one element is held in memory at once, so arbitrarily large files can be processed.
+    pub fn iter_array<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<JsonArrayIter<T, BufReader<fs::File>>, Error> {
+        Ok(JsonArrayIter::new(BufReader::new(fs::File::open(file_path)?)))
+    }
+}
+
+impl Bincode {
+    /// Tries to load a bincode file into the required struct, reading it through a buffer instead of loading it whole into memory.
+    pub fn load_streaming<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<T, Error> {
+        let mut reader = BufReader::new(fs::File::open(file_path)?);
+        Ok(bincode::serde::decode_from_std_read::<T, _, _>(&mut reader, bincode::config::standard())?)
+    }
+}
+
+// #===========================#
+// #=== JSON ARRAY ITERATOR ===#
+
+/// Position of the iterator inside of the array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArrayState {
+    /// The opening bracket was not read yet
+    Start,
+    /// The next element follows
+    Element,
+    /// The array ended or an error occurred
+    Done,
+}
+
+/// Iterator over the elements of a top-level JSON array, deserializing one element at a time.
+pub struct JsonArrayIter<T, R: BufRead> {
+    reader: R,
+    state: ArrayState,
+    buffer: Vec<u8>,
+    _marker: PhantomData<fn() -> T>,
+}
+impl <T: for<'de> Deserialize<'de>, R: BufRead> JsonArrayIter<T, R> {
+    /// Creates the iterator over the array read from the reader.
+    pub fn new(reader: R) -> Self {
+        JsonArrayIter { reader, state: ArrayState::Start, buffer: Vec::new(), _marker: PhantomData }
+    }
+    /// Returns the next byte without consuming it.
+    fn peek(&mut self) -> Result<Option<u8>, Error> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+    /// Consumes the next byte.
+    fn bump(&mut self) -> Result<Option<u8>, Error> {
+        let byte = self.peek()?;
+        if byte.is_some() {
+            self.reader.consume(1);
+        }
+        Ok(byte)
+    }
+    /// Skips whitespace and returns the next byte without consuming it.
+    fn peek_token(&mut self) -> Result<Option<u8>, Error> {
+        while let Some(byte) = self.peek()? {
+            if !byte.is_ascii_whitespace() {
+                return Ok(Some(byte));
+            }
+            self.reader.consume(1);
+        }
+        Ok(None)
+    }
+    /// Reads the raw bytes of the next element into the buffer.
+    fn read_element(&mut self) -> Result<(), Error> {
+        self.buffer.clear();
+        let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+        while let Some(byte) = self.peek()? {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {},
+                }
+            } else {
+                match byte {
+                    b'"' => in_string = true,
+                    b'[' | b'{' => depth += 1,
+                    b']' | b'}' if depth > 0 => depth -= 1,
+                    // A scalar ends at the next delimiter
+                    b',' | b']' if depth == 0 => break,
+                    _ if depth == 0 && byte.is_ascii_whitespace() => break,
+                    _ => {},
+                }
+            }
+            self.buffer.push(byte);
+            self.reader.consume(1);
+
+            // A string, object or array ends with its closing character
+            if depth == 0 && !in_string && matches!(byte, b'"' | b']' | b'}') {
+                break;
+            }
+        }
+        Ok(())
+    }
+    /// Reads and deserializes the next element.
+    fn next_element(&mut self) -> Result<Option<T>, Error> {
+        if self.state == ArrayState::Start {
+            if self.peek_token()? != Some(b'[') {
+                return Err(syntax_error("expected a top-level array"));
+            }
+            self.bump()?;
+            if self.peek_token()? == Some(b']') {
+                return Ok(None);
+            }
+            self.state = ArrayState::Element;
+        }
+
+        self.peek_token()?;
+        self.read_element()?;
+        let element = serde_json::from_slice::<T>(&self.buffer)?;
+
+        // Expect a separator or the end of the array
+        match (self.peek_token()?, self.bump()?) {
+            (Some(b','), _) => {},
+            (Some(b']'), _) => self.state = ArrayState::Done,
+            _ => return Err(syntax_error("expected `,` or `]` after an array element")),
+        }
+        Ok(Some(element))
+    }
+}
+impl <T: for<'de> Deserialize<'de>, R: BufRead> Iterator for JsonArrayIter<T, R> {
+    type Item = Result<T, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.state == ArrayState::Done {
+            return None;
+        }
+        match self.next_element() {
+            Ok(Some(element)) => Some(Ok(element)),
+            Ok(None) => {
+                self.state = ArrayState::Done;
+                None
+            },
+            Err(error) => {
+                self.state = ArrayState::Done;
+                Some(Err(error))
+            },
+        }
+    }
+}
+
+/// Creates a JSON syntax error with the message.
+fn syntax_error(message: &str) -> Error {
+    Error::Json(serde_json::Error::io(std::io::Error::new(std::io::ErrorKind::InvalidData, message)))
+}
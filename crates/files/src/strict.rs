@@ -0,0 +1,139 @@
+use std::fmt::Display;
+use serde::Deserialize;
+
+use crate::{Error, Format, Json, Toml, document::{Document, parse_key_path, read_document}};
+
+#[test]
+fn test() {
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Config { port: u16, #[serde(alias = "hostname")] host: String }
+
+    let path = std::env::temp_dir().join(format!("util_files_strict_{}.toml", std::process::id()));
+    let path = path.to_str().unwrap();
+    std::fs::write(path, "port = 80\nprot = 81\nhostname = \"localhost\"\n").unwrap();
+
+    let strict = Strict::warn().deprecate("hostname", "host");
+    let (_, warnings) = Toml::load_strict::<Config>(path, &strict).unwrap();
+    assert_eq!(warnings, vec![
+        KeyWarning::Deprecated { path: "hostname".into(), replacement: "host".into() },
+        KeyWarning::Unknown("prot".into()),
+    ]);
+    assert!(matches!(Toml::load_strict::<Config>(path, &Strict::deny()), Err(Error::StrictKeys(_))));
+    std::fs::remove_file(path).unwrap();
+}
+
+// #======================#
+// #=== STRICT OPTIONS ===#
+
+/// How reported keys are handled by strict loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrictMode {
+    /// Return the reported keys as warnings alongside the loaded struct
+    #[default]
+    Warn,
+    /// Fail with [`Error::StrictKeys`] if any key is reported
+    Deny,
+}
+
+/// Options for strict loading, detecting keys not used by the struct and deprecated keys.
+#[derive(Debug, Clone, Default)]
+pub struct Strict {
+    /// How the reported keys are handled
+    pub mode: StrictMode,
+    /// Deprecated key paths with their replacement hints
+    deprecated: Vec<(String, String)>,
+}
+impl Strict {
+    /// Creates options that return reported keys as warnings.
+    pub fn warn() -> Self {
+        Strict { mode: StrictMode::Warn, deprecated: Vec::new() }
+    }
+    /// Creates options that fail if any key is reported.
+    pub fn deny() -> Self {
+        Strict { mode: StrictMode::Deny, deprecated: Vec::new() }
+    }
+    /// Marks the dotted key path as deprecated, pointing the user to the replacement.
+    pub fn deprecate(mut self, key_path: &str, replacement: &str) -> Self {
+        self.deprecated.push((key_path.to_string(), replacement.to_string()));
+        self
+    }
+}
+
+/// Key reported by strict loading.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyWarning {
+    /// Key present in the file but not used by the struct
+    Unknown (String),
+    /// Key marked as deprecated
+    Deprecated { path: String, replacement: String },
+}
+impl Display for KeyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyWarning::Unknown(path) => write!(f, "unknown key `{path}`"),
+            KeyWarning::Deprecated { path, replacement } => write!(f, "deprecated key `{path}`, use `{replacement}` instead"),
+        }
+    }
+}
+
+// #======================#
+// #=== STRICT LOADING ===#
+
+/// Formats the path of an unused key as a dotted key path.
+fn format_path(path: &serde_ignored::Path) -> String {
+    match path {
+        serde_ignored::Path::Root => String::new(),
+        serde_ignored::Path::Seq { parent, index } => format!("{}[{index}]", format_path(parent)),
+        serde_ignored::Path::Map { parent, key } => match format_path(parent) {
+            parent if parent.is_empty() => key.clone(),
+            parent => format!("{parent}.{key}"),
+        },
+        serde_ignored::Path::Some { parent } | serde_ignored::Path::NewtypeStruct { parent } | serde_ignored::Path::NewtypeVariant { parent } => format_path(parent),
+    }
+}
+
+/// Loads the document into the struct, reporting unknown and deprecated keys.
+fn load_strict<F: Format, D: Document, T: for<'de> Deserialize<'de>>(file_path: &str, strict: &Strict) -> Result<(T, Vec<KeyWarning>), Error> {
+    let document = read_document::<F, D>(file_path)?;
+
+    // Report deprecated keys present in the file
+    let mut warnings = Vec::new();
+    for (key_path, replacement) in &strict.deprecated {
+        let mut node = Some(&document);
+        for segment in parse_key_path(key_path)? {
+            node = node.and_then(|node| node.child(&segment));
+        }
+        if node.is_some() {
+            warnings.push(KeyWarning::Deprecated { path: key_path.clone(), replacement: replacement.clone() });
+        }
+    }
+
+    // Report keys not used by the struct, unless already reported as deprecated
+    let mut unknown = Vec::new();
+    let content = document.into_struct_ignored::<T>(|path| unknown.push(format_path(&path)))?;
+    for path in unknown {
+        if !strict.deprecated.iter().any(|(key_path, _)| *key_path == path) {
+            warnings.push(KeyWarning::Unknown(path));
+        }
+    }
+
+    match strict.mode {
+        StrictMode::Deny if !warnings.is_empty() => Err(Error::StrictKeys(warnings)),
+        _ => Ok((content, warnings)),
+    }
+}
+
+impl Toml {
+    /// Tries to load a TOML file into the required struct, reporting keys the struct did not use and deprecated keys.
+    pub fn load_strict<T: for<'de> Deserialize<'de>>(file_path: &str, strict: &Strict) -> Result<(T, Vec<KeyWarning>), Error> {
+        load_strict::<Self, toml::Value, T>(file_path, strict)
+    }
+}
+
+impl Json {
+    /// Tries to load a JSON file into the required struct, reporting keys the struct did not use and deprecated keys.
+    pub fn load_strict<T: for<'de> Deserialize<'de>>(file_path: &str, strict: &Strict) -> Result<(T, Vec<KeyWarning>), Error> {
+        load_strict::<Self, serde_json::Value, T>(file_path, strict)
+    }
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, write_atomic};
+
+#[test]
+fn test() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Row { name: String, score: u32 }
+
+    let path = std::env::temp_dir().join(format!("util_files_tabular_{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    let rows = vec![Row { name: "a;b".into(), score: 1 }, Row { name: "c".into(), score: 2 }];
+
+    let options = CsvOptions::new().delimiter(b';');
+    Csv::create_with(path, &rows, &options).unwrap();
+    assert_eq!(fs::read_to_string(path).unwrap(), "name;score\n\"a;b\";1\nc;2\n");
+    assert_eq!(Csv::load_with::<Row>(path, &options).unwrap(), rows);
+
+    // Broken rows are reported with the line they start on, counting the header and line breaks in quoted fields
+    fs::write(path, "name,score\n\"a\nb\",1\nb,x\nc,3\n").unwrap();
+    let results: Vec<_> = Csv::iter::<Row>(path).unwrap().collect();
+    assert_eq!(results.len(), 3);
+    assert!(matches!(results[1], Err(Error::CsvRow { line: 4, .. })));
+    assert!(matches!(Csv::load::<Row>(path), Err(Error::CsvRow { line: 4, .. })));
+    fs::remove_file(path).unwrap();
+}
+
+// #===================#
+// #=== CSV OPTIONS ===#
+
+/// When fields are quoted in written CSV files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CsvQuoting {
+    /// Quote every field
+    Always,
+    /// Quote only fields containing the delimiter, quotes or line breaks
+    #[default]
+    Necessary,
+    /// Quote every field that is not a number
+    NonNumeric,
+    /// Never quote fields, even if it produces invalid CSV
+    Never,
+}
+impl From<CsvQuoting> for csv::QuoteStyle {
+    fn from(value: CsvQuoting) -> Self {
+        match value {
+            CsvQuoting::Always => csv::QuoteStyle::Always,
+            CsvQuoting::Necessary => csv::QuoteStyle::Necessary,
+            CsvQuoting::NonNumeric => csv::QuoteStyle::NonNumeric,
+            CsvQuoting::Never => csv::QuoteStyle::Never,
+        }
+    }
+}
+
+/// Options for reading and writing CSV files.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    delimiter: u8,
+    quote: u8,
+    quoting: CsvQuoting,
+    headers: bool,
+}
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions { delimiter: b',', quote: b'"', quoting: CsvQuoting::Necessary, headers: true }
+    }
+}
+impl CsvOptions {
+    /// Creates the default options, comma separated with a header row.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the field delimiter, e.g. `b';'` or `b'\t'`.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+    /// Sets the quote character.
+    pub fn quote(mut self, quote: u8) -> Self {
+        self.quote = quote;
+        self
+    }
+    /// Sets when fields are quoted in written files.
+    pub fn quoting(mut self, quoting: CsvQuoting) -> Self {
+        self.quoting = quoting;
+        self
+    }
+    /// Sets if the first row holds the field names.
+    pub fn headers(mut self, headers: bool) -> Self {
+        self.headers = headers;
+        self
+    }
+    /// Creates the reader over the source.
+    fn reader<R: std::io::Read>(&self, source: R) -> csv::Reader<R> {
+        csv::ReaderBuilder::new().delimiter(self.delimiter).quote(self.quote).has_headers(self.headers).from_reader(source)
+    }
+    /// Creates the writer into memory.
+    fn writer(&self) -> csv::Writer<Vec<u8>> {
+        csv::WriterBuilder::new().delimiter(self.delimiter).quote(self.quote).quote_style(self.quoting.into()).has_headers(self.headers).from_writer(Vec::new())
+    }
+}
+
+// #==========================#
+// #=== CSV IMPLEMENTATION ===#
+
+/// Unit struct holding methods for interacting with CSV files holding one struct per row.
+pub struct Csv;
+impl Csv {
+    /// Tries to create a new CSV file from the rows provided.
+    pub fn create<T: Serialize>(file_path: &str, rows: &[T]) -> Result<(), Error> {
+        Self::create_with(file_path, rows, &CsvOptions::default())
+    }
+    /// Tries to create a new CSV file from the rows provided using the options.
+    pub fn create_with<T: Serialize>(file_path: &str, rows: &[T], options: &CsvOptions) -> Result<(), Error> {
+        write_atomic(file_path, &Self::to_bytes(rows, options)?)
+    }
+    /// Tries to save the rows to an existing CSV file.
+    pub fn save<T: Serialize>(file_path: &str, rows: &[T]) -> Result<(), Error> {
+        Self::save_with(file_path, rows, &CsvOptions::default())
+    }
+    /// Tries to save the rows to an existing CSV file using the options.
+    pub fn save_with<T: Serialize>(file_path: &str, rows: &[T], options: &CsvOptions) -> Result<(), Error> {
+        fs::metadata(file_path)?;
+        Self::create_with(file_path, rows, options)
+    }
+    /// Tries to load all rows of a CSV file. Fails on the first broken row, reporting the file line it starts on.
+    pub fn load<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<Vec<T>, Error> {
+        Self::load_with(file_path, &CsvOptions::default())
+    }
+    /// Tries to load all rows of a CSV file using the options. Fails on the first broken row, reporting the file line it starts on.
+    pub fn load_with<T: for<'de> Deserialize<'de>>(file_path: &str, options: &CsvOptions) -> Result<Vec<T>, Error> {
+        Self::iter_with(file_path, options)?.collect()
+    }
+    /// Tries to open a CSV file, iterating over its rows one at a time.
+    /// Broken rows are reported as [`Error::CsvRow`] with the file line they start on, the iteration continues with the next row.
+    pub fn iter<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<impl Iterator<Item = Result<T, Error>> + use<T>, Error> {
+        Self::iter_with(file_path, &CsvOptions::default())
+    }
+    /// Tries to open a CSV file using the options, iterating over its rows one at a time.
+    /// Broken rows are reported as [`Error::CsvRow`] with the file line they start on, the iteration continues with the next row.
+    pub fn iter_with<T: for<'de> Deserialize<'de>>(file_path: &str, options: &CsvOptions) -> Result<impl Iterator<Item = Result<T, Error>> + use<T>, Error> {
+        Ok(Self::records(options.reader(fs::File::open(file_path)?)))
+    }
+    /// Serializes the rows to CSV using the options.
+    fn to_bytes<T: Serialize>(rows: &[T], options: &CsvOptions) -> Result<Vec<u8>, Error> {
+        let mut writer = options.writer();
+        for row in rows {
+            writer.serialize(row)?;
+        }
+        writer.into_inner().map_err(|error| Error::IO(error.into_error()))
+    }
+    /// Deserializes the rows of the reader, reporting broken rows with the file line they start on.
+    fn records<T: for<'de> Deserialize<'de>, R: std::io::Read>(reader: csv::Reader<R>) -> impl Iterator<Item = Result<T, Error>> + use<T, R> {
+        let mut records = reader.into_deserialize::<T>();
+        std::iter::from_fn(move || {
+            // Errors without a position are reported at the line where reading continued
+            let line = records.reader().position().line();
+            let row = records.next()?;
+            Some(row.map_err(|error| Error::CsvRow { line: error.position().map_or(line, csv::Position::line), error }))
+        })
+    }
+}
+
+// #=====================#
+// #=== ASYNC METHODS ===#
+
+#[cfg(feature = "tokio")]
+impl Csv {
+    /// Async version of [`Csv::create`] using non-blocking file IO.
+    pub async fn create_async<T: Serialize>(file_path: &str, rows: &[T]) -> Result<(), Error> {
+        Self::create_with_async(file_path, rows, &CsvOptions::default()).await
+    }
+    /// Async version of [`Csv::create_with`] using non-blocking file IO.
+    pub async fn create_with_async<T: Serialize>(file_path: &str, rows: &[T], options: &CsvOptions) -> Result<(), Error> {
+        crate::write_atomic_async(file_path, &Self::to_bytes(rows, options)?, None).await
+    }
+    /// Async version of [`Csv::save`] using non-blocking file IO.
+    pub async fn save_async<T: Serialize>(file_path: &str, rows: &[T]) -> Result<(), Error> {
+        Self::save_with_async(file_path, rows, &CsvOptions::default()).await
+    }
+    /// Async version of [`Csv::save_with`] using non-blocking file IO.
+    pub async fn save_with_async<T: Serialize>(file_path: &str, rows: &[T], options: &CsvOptions) -> Result<(), Error> {
+        tokio::fs::metadata(file_path).await?;
+        Self::create_with_async(file_path, rows, options).await
+    }
+    /// Async version of [`Csv::load`] using non-blocking file IO. The whole file is read into memory before parsing.
+    pub async fn load_async<T: for<'de> Deserialize<'de>>(file_path: &str) -> Result<Vec<T>, Error> {
+        Self::load_with_async(file_path, &CsvOptions::default()).await
+    }
+    /// Async version of [`Csv::load_with`] using non-blocking file IO. The whole file is read into memory before parsing.
+    pub async fn load_with_async<T: for<'de> Deserialize<'de>>(file_path: &str, options: &CsvOptions) -> Result<Vec<T>, Error> {
+        let content = tokio::fs::read(file_path).await?;
+        Self::records(options.reader(content.as_slice())).collect()
+    }
+}
@@ -0,0 +1,134 @@
+use std::{fs, io::ErrorKind, path::{Path, PathBuf}, sync::atomic::Ordering};
+use serde::Serialize;
+
+use crate::{Error, Format, TEMP_COUNTER, WriteOptions, write_atomic};
+
+#[test]
+fn test() {
+    let file = TempFile::with_content::<crate::Toml, _>(&toml::toml! { value = 1 }).unwrap();
+    assert!(file.path_str().unwrap().ends_with(".toml"));
+    assert_eq!(crate::Toml::load::<toml::Table>(file.path_str().unwrap()).unwrap()["value"].as_integer(), Some(1));
+    let path = file.path().to_path_buf();
+    drop(file);
+    assert!(!path.exists());
+    let file = TempFile::with_content_with::<crate::Json, _>(&[1], &crate::WriteOptions::new().compact().trailing_newline(false)).unwrap();
+    assert_eq!(fs::read_to_string(file.path()).unwrap(), "[1]");
+
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.join("a.txt"), "a").unwrap();
+    let path = dir.persist();
+    assert!(path.join("a.txt").exists());
+    fs::remove_dir_all(path).unwrap();
+}
+
+/// Returns a unique, not yet existing path in the system temporary directory.
+fn unique_path(extension: &str) -> PathBuf {
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|time| time.subsec_nanos()).unwrap_or_default();
+    let count = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut path = std::env::temp_dir().join(format!("util_files_{}_{count}_{nanos}", std::process::id()));
+    if !extension.is_empty() {
+        path.set_extension(extension);
+    }
+    path
+}
+
+/// Creates a new entry at a unique temporary path, retrying when the name is already taken.
+fn create_unique(extension: &str, create: impl Fn(&Path) -> std::io::Result<()>) -> Result<PathBuf, Error> {
+    loop {
+        let path = unique_path(extension);
+        match create(&path) {
+            Ok(()) => return Ok(path),
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error.into()),
+        }
+    }
+}
+
+// #=================#
+// #=== TEMP FILE ===#
+
+/// File in the system temporary directory (`$TMPDIR`) removed when dropped.
+/// Call [`TempFile::persist`] to keep it, e.g. for inspecting a failed test.
+#[derive(Debug)]
+pub struct TempFile {
+    path: PathBuf,
+    persist: bool,
+}
+impl TempFile {
+    /// Creates a new empty temporary file with the extension (without the leading dot, may be empty).
+    pub fn new(extension: &str) -> Result<Self, Error> {
+        let path = create_unique(extension, |path| fs::File::create_new(path).map(|_| ()))?;
+        Ok(TempFile { path, persist: false })
+    }
+    /// Creates a new temporary file holding the struct serialized in the format, using the format's extension.
+    pub fn with_content<F: Format, T: Serialize + ?Sized>(content: &T) -> Result<Self, Error> {
+        let file = Self::new(F::EXTENSION)?;
+        write_atomic(&file.path, &F::to_bytes(content)?)?;
+        Ok(file)
+    }
+    /// Creates a new temporary file holding the struct serialized in the format and written with the options.
+    pub fn with_content_with<F: Format, T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<Self, Error> {
+        let file = Self::new(F::EXTENSION)?;
+        write_atomic(&file.path, &F::to_bytes_with(content, options)?)?;
+        Ok(file)
+    }
+    /// Returns the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// Returns the path of the file as a string, as expected by the format methods.
+    /// Returns `None` if the path is not valid UTF-8, e.g. because `$TMPDIR` is not.
+    pub fn path_str(&self) -> Option<&str> {
+        self.path.to_str()
+    }
+    /// Keeps the file on disk and returns its path.
+    pub fn persist(mut self) -> PathBuf {
+        self.persist = true;
+        std::mem::take(&mut self.path)
+    }
+}
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persist {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+// #================#
+// #=== TEMP DIR ===#
+
+/// Directory in the system temporary directory (`$TMPDIR`) removed with all its content when dropped.
+/// Call [`TempDir::persist`] to keep it, e.g. for inspecting a failed test.
+#[derive(Debug)]
+pub struct TempDir {
+    path: PathBuf,
+    persist: bool,
+}
+impl TempDir {
+    /// Creates a new empty temporary directory.
+    pub fn new() -> Result<Self, Error> {
+        let path = create_unique("", |path| fs::create_dir(path))?;
+        Ok(TempDir { path, persist: false })
+    }
+    /// Returns the path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    /// Returns the path of the entry inside the directory.
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.join(path)
+    }
+    /// Keeps the directory on disk and returns its path.
+    pub fn persist(mut self) -> PathBuf {
+        self.persist = true;
+        std::mem::take(&mut self.path)
+    }
+}
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if !self.persist {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
+}
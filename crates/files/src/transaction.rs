@@ -0,0 +1,163 @@
+use std::{fs, io::Write, path::{Path, PathBuf}};
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Format, Json, WriteOptions, temp_sibling, write_atomic};
+
+#[test]
+fn test() {
+    let dir = std::env::temp_dir().join(format!("util_files_transaction_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let journal = dir.join("settings.journal");
+
+    let mut transaction = Transaction::open(&journal).unwrap();
+    transaction.stage::<crate::Toml, _>(dir.join("a.toml"), &toml::toml! { value = 1 }).unwrap();
+    transaction.write(dir.join("b.txt"), b"b".to_vec());
+    transaction.stage_with::<crate::Json, _>(dir.join("c.json"), &[1, 2], &crate::WriteOptions::new().compact()).unwrap();
+    transaction.commit().unwrap();
+    assert_eq!(fs::read_to_string(dir.join("c.json")).unwrap(), "[1,2]\n");
+    assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "b");
+    assert!(!journal.exists());
+
+    // Simulate a crash after the commit point, the next open finishes the transaction
+    let temp = temp_sibling(&dir.join("b.txt"));
+    fs::write(&temp, "c").unwrap();
+    let entries = vec![JournalEntry { temp, target: dir.join("b.txt") }];
+    write_atomic(&journal, &Json::to_bytes(&Journal { state: JournalState::Committed, entries }).unwrap()).unwrap();
+    Transaction::open(&journal).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("b.txt")).unwrap(), "c");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+// #===============#
+// #=== JOURNAL ===#
+
+/// State of the transaction recorded in the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JournalState {
+    /// Temporary files are being written, recovery removes them
+    Pending,
+    /// All temporary files are written, recovery renames them over their targets
+    Committed,
+}
+
+/// Temporary file waiting to replace its target.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    temp: PathBuf,
+    target: PathBuf,
+}
+
+/// Journal describing a transaction in progress.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Journal {
+    state: JournalState,
+    entries: Vec<JournalEntry>,
+}
+impl Journal {
+    /// Atomically writes the journal to the file.
+    fn write(&self, journal_path: &Path) -> Result<(), Error> {
+        write_atomic(journal_path, &Json::to_bytes(self)?)
+    }
+    /// Finishes the transaction described by the journal and removes the journal.
+    fn apply(&self, journal_path: &Path) -> Result<(), Error> {
+        for entry in &self.entries {
+            match self.state {
+                // Roll back by removing the temporary files
+                JournalState::Pending => if let Err(error) = fs::remove_file(&entry.temp) && error.kind() != std::io::ErrorKind::NotFound {
+                    return Err(error.into());
+                },
+                // Roll forward by renaming the remaining temporary files
+                JournalState::Committed => if entry.temp.exists() {
+                    fs::rename(&entry.temp, &entry.target)?;
+                },
+            }
+        }
+        Ok(fs::remove_file(journal_path)?)
+    }
+}
+
+// #===================#
+// #=== TRANSACTION ===#
+
+/// Set of file writes applied together. Either all files are replaced or none of them.
+///
+/// Writes are staged in memory, then written to temporary files next to their targets and renamed over them.
+/// A journal file records the progress, so a transaction interrupted by a crash is rolled back or finished
+/// by the next [`Transaction::open`] with the same journal path.
+pub struct Transaction {
+    journal_path: PathBuf,
+    staged: Vec<(PathBuf, Vec<u8>)>,
+}
+impl Transaction {
+    /// Opens a new transaction using the journal file, recovering any half-applied transaction first.
+    pub fn open(journal_path: impl AsRef<Path>) -> Result<Self, Error> {
+        let journal_path = journal_path.as_ref().to_path_buf();
+        Self::recover(&journal_path)?;
+        Ok(Transaction { journal_path, staged: Vec::new() })
+    }
+    /// Recovers a half-applied transaction recorded in the journal file, if there is one.
+    pub fn recover(journal_path: impl AsRef<Path>) -> Result<(), Error> {
+        let journal_path = journal_path.as_ref();
+        match fs::read(journal_path) {
+            Ok(content) => Json::from_bytes::<Journal>(&content)?.apply(journal_path),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error.into()),
+        }
+    }
+    /// Stages raw content to be written to the path. Staging the same path again replaces the content.
+    pub fn write(&mut self, file_path: impl AsRef<Path>, content: Vec<u8>) {
+        let file_path = file_path.as_ref().to_path_buf();
+        self.staged.retain(|(path, _)| *path != file_path);
+        self.staged.push((file_path, content));
+    }
+    /// Stages the struct serialized in the format to be written to the path.
+    pub fn stage<F: Format, T: Serialize + ?Sized>(&mut self, file_path: impl AsRef<Path>, content: &T) -> Result<(), Error> {
+        self.write(file_path, F::to_bytes(content)?);
+        Ok(())
+    }
+    /// Stages the struct serialized in the format and written with the options to be written to the path.
+    pub fn stage_with<F: Format, T: Serialize + ?Sized>(&mut self, file_path: impl AsRef<Path>, content: &T, options: &WriteOptions) -> Result<(), Error> {
+        self.write(file_path, F::to_bytes_with(content, options)?);
+        Ok(())
+    }
+    /// Returns the paths of all staged writes.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.staged.iter().map(|(path, _)| path.as_path())
+    }
+    /// Writes all staged files. If writing fails before the commit point, all changes are rolled back.
+    /// If renaming fails after the commit point, the transaction is finished by the next [`Transaction::open`].
+    pub fn commit(self) -> Result<(), Error> {
+        if self.staged.is_empty() {
+            return Ok(());
+        }
+
+        // Record the temporary files before creating them
+        let mut journal = Journal {
+            state: JournalState::Pending,
+            entries: self.staged.iter().map(|(target, _)| JournalEntry { temp: temp_sibling(target), target: target.clone() }).collect(),
+        };
+        journal.write(&self.journal_path)?;
+
+        // Write all temporary files, rolling back on failure
+        let written = journal.entries.iter().zip(&self.staged).try_for_each(|(entry, (_, content))| {
+            let mut file = fs::File::create_new(&entry.temp)?;
+            if let Ok(metadata) = fs::metadata(&entry.target) {
+                file.set_permissions(metadata.permissions())?;
+            }
+            file.write_all(content)?;
+            file.sync_all()
+        });
+        if let Err(error) = written {
+            journal.apply(&self.journal_path)?;
+            return Err(error.into());
+        }
+
+        // Commit point, from now on the transaction is always finished
+        journal.state = JournalState::Committed;
+        journal.write(&self.journal_path)?;
+        journal.apply(&self.journal_path)
+    }
+    /// Discards all staged writes without touching any file.
+    pub fn rollback(self) {}
+}
@@ -0,0 +1,325 @@
+use std::{fs, path::{Path, PathBuf}};
+use glob::Pattern;
+
+use crate::{Error, temp_sibling, walk_files, manifest::{hash_file, portable_path}};
+
+#[test]
+fn test() {
+    let dir = std::env::temp_dir().join(format!("util_files_transfer_{}", std::process::id()));
+    let (source, target) = (dir.join("source"), dir.join("target"));
+    fs::create_dir_all(source.join("nested")).unwrap();
+    fs::write(source.join("a.txt"), "a").unwrap();
+    fs::write(source.join("nested/b.txt"), "bb").unwrap();
+    fs::write(source.join("nested/c.log"), "c").unwrap();
+
+    let mut events = 0;
+    let progress = copy_dir(&source, &target, &mut TransferOptions::new().exclude("*.log").unwrap().on_progress(|_| events += 1)).unwrap();
+    assert_eq!((progress.files_done, progress.bytes_done, events), (2, 3, 2));
+    assert!(!target.join("nested/c.log").exists());
+
+    // Only changed files are copied, extras are deleted
+    fs::write(source.join("a.txt"), "aa").unwrap();
+    fs::write(target.join("extra.txt"), "x").unwrap();
+    let progress = sync_dir(&source, &target, &mut TransferOptions::new().compare(Compare::Hash).delete_extra(true)).unwrap();
+    assert_eq!(progress.files_done, 2);
+    assert_eq!(fs::read_to_string(target.join("a.txt")).unwrap(), "aa");
+    assert!(!target.join("extra.txt").exists());
+    assert_eq!(sync_dir(&source, &target, &mut TransferOptions::new()).unwrap().files_done, 0);
+
+    move_dir(&source, dir.join("moved"), &mut TransferOptions::new()).unwrap();
+    assert!(!source.exists() && dir.join("moved/nested/c.log").exists());
+
+    // Moving into an existing directory copies empty directories and symbolic links too
+    fs::create_dir_all(dir.join("moved/empty")).unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("a.txt", dir.join("moved/link")).unwrap();
+    move_dir(dir.join("moved"), &target, &mut TransferOptions::new()).unwrap();
+    assert!(!dir.join("moved").exists() && target.join("empty").is_dir());
+    #[cfg(unix)]
+    assert_eq!(fs::read_link(target.join("link")).unwrap(), Path::new("a.txt"));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+// #===============#
+// #=== OPTIONS ===#
+
+/// How sync decides that a file changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compare {
+    /// Files differ if their sizes differ
+    Size,
+    /// Files differ if their sizes or modification times differ
+    #[default]
+    SizeAndModified,
+    /// Files differ if their SHA-256 hashes differ
+    Hash,
+}
+
+/// Progress of a transfer, reported after every processed file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of files processed so far
+    pub files_done: u64,
+    /// Number of files to process
+    pub files_total: u64,
+    /// Number of bytes processed so far
+    pub bytes_done: u64,
+    /// Number of bytes to process
+    pub bytes_total: u64,
+    /// Relative path of the last processed file
+    pub current: PathBuf,
+}
+
+/// Callback receiving the progress of a transfer.
+type ProgressCallback<'a> = Box<dyn FnMut(&Progress) + 'a>;
+
+/// Options for [`copy_dir`], [`move_dir`] and [`sync_dir`].
+#[derive(Default)]
+pub struct TransferOptions<'a> {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    compare: Compare,
+    delete_extra: bool,
+    progress: Option<ProgressCallback<'a>>,
+}
+impl <'a> TransferOptions<'a> {
+    /// Creates options transferring all files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Only transfers files whose relative path (with `/` separators) matches the glob. Can be called multiple times.
+    pub fn include(mut self, pattern: &str) -> Result<Self, Error> {
+        self.include.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+    /// Skips files whose relative path (with `/` separators) matches the glob. Can be called multiple times.
+    pub fn exclude(mut self, pattern: &str) -> Result<Self, Error> {
+        self.exclude.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+    /// Sets how sync decides that a file changed.
+    pub fn compare(mut self, compare: Compare) -> Self {
+        self.compare = compare;
+        self
+    }
+    /// Makes sync delete files in the target that are not in the source. Excluded files are never deleted.
+    pub fn delete_extra(mut self, delete_extra: bool) -> Self {
+        self.delete_extra = delete_extra;
+        self
+    }
+    /// Sets the callback reporting the progress after every processed file.
+    pub fn on_progress(mut self, callback: impl FnMut(&Progress) + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+    /// Checks if the relative path passes the include and exclude filters.
+    fn matches(&self, relative: &Path) -> bool {
+        let name = portable_path(relative);
+        (self.include.is_empty() || self.include.iter().any(|pattern| pattern.matches(&name)))
+            && !self.exclude.iter().any(|pattern| pattern.matches(&name))
+    }
+    /// Returns the filtered relative paths of all files under the directory.
+    fn files(&self, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        Ok(walk_files(dir)?.into_iter().filter(|relative| self.matches(relative)).collect())
+    }
+    /// Returns true if the filters let every file through.
+    fn is_unfiltered(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+}
+
+// #=================#
+// #=== TRANSFERS ===#
+
+/// Copies the file next to the target and renames it over the target, keeping the modification time.
+fn copy_file(source: &Path, target: &Path) -> Result<(), Error> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = temp_sibling(target);
+    let result = (|| {
+        fs::copy(source, &temp_path)?;
+        fs::File::options().write(true).open(&temp_path)?.set_modified(fs::metadata(source)?.modified()?)?;
+        fs::rename(&temp_path, target)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    Ok(result?)
+}
+
+/// Copies the listed files, reporting the progress.
+fn copy_files(source: &Path, target: &Path, files: &[PathBuf], options: &mut TransferOptions) -> Result<Progress, Error> {
+    let mut progress = Progress { files_total: files.len() as u64, ..Default::default() };
+    for relative in files {
+        progress.bytes_total += fs::metadata(source.join(relative))?.len();
+    }
+    for relative in files {
+        copy_file(&source.join(relative), &target.join(relative))?;
+        progress.files_done += 1;
+        progress.bytes_done += fs::metadata(target.join(relative))?.len();
+        progress.current = relative.clone();
+        if let Some(callback) = &mut options.progress {
+            callback(&progress);
+        }
+    }
+    Ok(progress)
+}
+
+/// Directories and symbolic links of a directory tree, relative to the directory.
+#[derive(Default)]
+struct Tree {
+    dirs: Vec<PathBuf>,
+    links: Vec<PathBuf>,
+}
+impl Tree {
+    /// Collects the tree of the directory. Fails on special files like sockets, which cannot be moved by copying.
+    fn walk(dir: &Path) -> Result<Self, Error> {
+        let mut tree = Tree::default();
+        let mut pending = vec![PathBuf::new()];
+        while let Some(relative) = pending.pop() {
+            for entry in fs::read_dir(dir.join(&relative))? {
+                let entry = entry?;
+                let (file_type, path) = (entry.file_type()?, relative.join(entry.file_name()));
+                if file_type.is_dir() {
+                    tree.dirs.push(path.clone());
+                    pending.push(path);
+                } else if file_type.is_symlink() {
+                    tree.links.push(path);
+                } else if !file_type.is_file() {
+                    return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("cannot move the special file {}", dir.join(path).display())).into());
+                }
+            }
+        }
+        tree.dirs.sort();
+        tree.links.sort();
+        Ok(tree)
+    }
+}
+
+/// Recreates the symbolic link at the target, replacing an existing file or link.
+fn copy_link(source: &Path, target: &Path) -> Result<(), Error> {
+    #[cfg(unix)]
+    {
+        let link = fs::read_link(source)?;
+        match fs::symlink_metadata(target) {
+            Ok(metadata) if !metadata.is_dir() => fs::remove_file(target)?,
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => return Err(error.into()),
+            _ => {},
+        }
+        Ok(std::os::unix::fs::symlink(link, target)?)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = target;
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("cannot move the symbolic link {}", source.display())).into())
+    }
+}
+
+/// Checks if the target file differs from the source file.
+fn differs(source: &Path, target: &Path, compare: Compare) -> Result<bool, Error> {
+    let (source_meta, target_meta) = match (fs::metadata(source), fs::metadata(target)) {
+        (Ok(source_meta), Ok(target_meta)) => (source_meta, target_meta),
+        (_, Err(error)) if error.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+        (Err(error), _) | (_, Err(error)) => return Err(error.into()),
+    };
+    if source_meta.len() != target_meta.len() {
+        return Ok(true);
+    }
+    Ok(match compare {
+        Compare::Size => false,
+        Compare::SizeAndModified => source_meta.modified()? != target_meta.modified()?,
+        Compare::Hash => hash_file(source)? != hash_file(target)?,
+    })
+}
+
+/// Recursively copies all files passing the filters from the source directory into the target directory.
+/// Existing files are overwritten. Returns the final progress.
+pub fn copy_dir(source: impl AsRef<Path>, target: impl AsRef<Path>, options: &mut TransferOptions) -> Result<Progress, Error> {
+    let (source, target) = (source.as_ref(), target.as_ref());
+    let files = options.files(source)?;
+    fs::create_dir_all(target)?;
+    copy_files(source, target, &files, options)
+}
+
+/// Moves all files passing the filters from the source directory into the target directory.
+/// Without filters the directory is renamed, falling back to copy and delete across file systems or into an existing target.
+/// The fallback also recreates empty directories and symbolic links, and only deletes what it copied.
+/// With filters only the matching files are moved and the source directories are left in place.
+pub fn move_dir(source: impl AsRef<Path>, target: impl AsRef<Path>, options: &mut TransferOptions) -> Result<Progress, Error> {
+    let (source, target) = (source.as_ref(), target.as_ref());
+    let files = options.files(source)?;
+
+    // Try to rename the whole directory at once
+    if options.is_unfiltered() && !target.exists() {
+        match fs::rename(source, target) {
+            Ok(()) => {
+                let mut progress = Progress { files_total: files.len() as u64, files_done: files.len() as u64, ..Default::default() };
+                for relative in &files {
+                    progress.bytes_total += fs::metadata(target.join(relative))?.len();
+                }
+                progress.bytes_done = progress.bytes_total;
+                progress.current = files.last().cloned().unwrap_or_default();
+                if let Some(callback) = &mut options.progress {
+                    callback(&progress);
+                }
+                return Ok(progress);
+            },
+            Err(error) if error.kind() == std::io::ErrorKind::CrossesDevices => {},
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    // Copy the files and the rest of the tree
+    let tree = match options.is_unfiltered() {
+        true => Tree::walk(source)?,
+        false => Tree::default(),
+    };
+    fs::create_dir_all(target)?;
+    let progress = copy_files(source, target, &files, options)?;
+    for relative in &tree.dirs {
+        fs::create_dir_all(target.join(relative))?;
+    }
+    for relative in &tree.links {
+        copy_link(&source.join(relative), &target.join(relative))?;
+    }
+
+    // Delete only what was copied, directories fail to delete if anything new was left behind
+    for relative in files.iter().chain(&tree.links) {
+        fs::remove_file(source.join(relative))?;
+    }
+    if options.is_unfiltered() {
+        for relative in tree.dirs.iter().rev() {
+            fs::remove_dir(source.join(relative))?;
+        }
+        fs::remove_dir(source)?;
+    }
+    Ok(progress)
+}
+
+/// One-way syncs the target directory with the source directory, copying only changed files passing the filters.
+/// Optionally deletes files from the target that are not in the source. Returns the final progress.
+pub fn sync_dir(source: impl AsRef<Path>, target: impl AsRef<Path>, options: &mut TransferOptions) -> Result<Progress, Error> {
+    let (source, target) = (source.as_ref(), target.as_ref());
+    let files = options.files(source)?;
+    fs::create_dir_all(target)?;
+
+    // Collect the changed files
+    let mut changed = Vec::new();
+    for relative in &files {
+        if differs(&source.join(relative), &target.join(relative), options.compare)? {
+            changed.push(relative.clone());
+        }
+    }
+    let progress = copy_files(source, target, &changed, options)?;
+
+    // Delete files missing from the source
+    if options.delete_extra {
+        for relative in options.files(target)? {
+            if files.binary_search(&relative).is_err() {
+                fs::remove_file(target.join(relative))?;
+            }
+        }
+    }
+    Ok(progress)
+}
@@ -0,0 +1,298 @@
+use std::{collections::BTreeMap, path::{Path, PathBuf}, sync::mpsc, thread, time::{Duration, Instant}};
+use glob::Pattern;
+use notify::{EventKind, RecursiveMode, Watcher as _, event::{ModifyKind, RenameMode}};
+
+use crate::{Error, manifest::portable_path};
+
+#[test]
+fn test() {
+    let dir = crate::TempDir::new().unwrap();
+
+    for polling in [false, true] {
+        let options = WatchOptions::new().debounce(Duration::from_millis(100)).poll_interval(Duration::from_millis(50)).force_polling(polling).exclude("*.tmp").unwrap();
+        let watcher = Watcher::new(dir.path(), options).unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        // Writes in quick succession are reported as a single event
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::write(dir.join("a.txt"), "b").unwrap();
+        std::fs::write(dir.join("a.tmp"), "a").unwrap();
+        let event = watcher.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(event.kind, WatchEventKind::Created);
+        assert_eq!(event.path.file_name().unwrap(), "a.txt");
+
+        std::fs::remove_file(dir.join("a.txt")).unwrap();
+        let event = watcher.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(event.kind, WatchEventKind::Removed);
+        assert!(watcher.recv_timeout(Duration::from_millis(300)).is_none());
+        std::fs::remove_file(dir.join("a.tmp")).unwrap();
+    }
+
+    // Pending changes are delivered when the watcher stops
+    let (sender, receiver) = mpsc::channel();
+    let options = WatchOptions::new().debounce(Duration::from_secs(60)).force_polling(true).poll_interval(Duration::from_millis(50));
+    let watcher = Watcher::with_callback(dir.path(), options, move |event| { let _ = sender.send(event); }).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    std::fs::write(dir.join("b.txt"), "b").unwrap();
+    thread::sleep(Duration::from_millis(300));
+    drop(watcher);
+    assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap().kind, WatchEventKind::Created);
+    std::fs::remove_file(dir.join("b.txt")).unwrap();
+
+    // Errors are reported as events
+    let event = error_event(notify::Error::generic("limit reached"), dir.path());
+    assert!(matches!(event.kind, WatchEventKind::Error { message } if message.contains("limit reached")) && event.path == dir.path());
+}
+
+// #==============#
+// #=== EVENTS ===#
+
+/// Kind of change reported by the [`Watcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEventKind {
+    /// The path was created
+    Created,
+    /// The content or metadata of the path changed
+    Modified,
+    /// The path was removed
+    Removed,
+    /// The path was renamed from another path. Only reported by native watchers, polling reports a removal and a creation
+    Renamed { from: PathBuf },
+    /// Watching the path failed, e.g. the watch limit was reached or the directory became unreadable.
+    /// Reported right away without debouncing, the path is the watched directory if the error names none
+    Error { message: String },
+}
+
+/// Debounced change of a single path inside the watched directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchEvent {
+    pub kind: WatchEventKind,
+    pub path: PathBuf,
+}
+
+// #===============#
+// #=== OPTIONS ===#
+
+/// Options for watching a directory.
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    debounce: Duration,
+    recursive: bool,
+    force_polling: bool,
+    poll_interval: Duration,
+}
+impl Default for WatchOptions {
+    fn default() -> Self {
+        WatchOptions {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            debounce: Duration::from_millis(200),
+            recursive: true,
+            force_polling: false,
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+impl WatchOptions {
+    /// Creates the default options, watching recursively with a debounce of 200ms.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Only reports paths whose relative path (with `/` separators) matches the glob. Can be called multiple times.
+    pub fn include(mut self, pattern: &str) -> Result<Self, Error> {
+        self.include.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+    /// Skips paths whose relative path (with `/` separators) matches the glob. Can be called multiple times.
+    pub fn exclude(mut self, pattern: &str) -> Result<Self, Error> {
+        self.exclude.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+    /// Sets how long a path must stay unchanged before its event is reported.
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+    /// Sets if subdirectories are watched too.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+    /// Sets if the directory is polled even when a native watcher is available, e.g. on network file systems.
+    pub fn force_polling(mut self, force_polling: bool) -> Self {
+        self.force_polling = force_polling;
+        self
+    }
+    /// Sets how often the directory is scanned when polling.
+    pub fn poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+    /// Checks if the path relative to the watched directory passes the filters.
+    fn matches(&self, relative: &Path) -> bool {
+        let name = portable_path(relative);
+        (self.include.is_empty() || self.include.iter().any(|pattern| pattern.matches(&name)))
+            && !self.exclude.iter().any(|pattern| pattern.matches(&name))
+    }
+}
+
+// #================#
+// #=== DEBOUNCE ===#
+
+/// Changes waiting until their path settles.
+#[derive(Default)]
+struct Pending {
+    events: BTreeMap<PathBuf, (WatchEventKind, Instant)>,
+}
+impl Pending {
+    /// Merges a new change of the path into the pending one.
+    fn push(&mut self, path: PathBuf, kind: WatchEventKind) {
+        let now = Instant::now();
+        let previous = self.events.remove(&path).map(|(kind, _)| kind);
+        let kind = match (previous, kind) {
+            // Created and removed again before anyone noticed
+            (Some(WatchEventKind::Created), WatchEventKind::Removed) => return,
+            (Some(WatchEventKind::Removed), WatchEventKind::Created) => WatchEventKind::Modified,
+            (Some(previous @ (WatchEventKind::Created | WatchEventKind::Renamed { .. })), WatchEventKind::Modified) => previous,
+            (_, WatchEventKind::Renamed { from }) => match self.events.remove(&from).map(|(kind, _)| kind) {
+                Some(WatchEventKind::Created) => WatchEventKind::Created,
+                _ => WatchEventKind::Renamed { from },
+            },
+            (_, kind) => kind,
+        };
+        self.events.insert(path, (kind, now));
+    }
+    /// Removes and returns all pending changes, settled or not.
+    fn drain(&mut self) -> Vec<WatchEvent> {
+        std::mem::take(&mut self.events).into_iter().map(|(path, (kind, _))| WatchEvent { kind, path }).collect()
+    }
+    /// Removes and returns all changes that settled for the debounce duration.
+    fn settled(&mut self, debounce: Duration) -> Vec<WatchEvent> {
+        let settled: Vec<PathBuf> = self.events.iter().filter(|(_, (_, seen))| seen.elapsed() >= debounce).map(|(path, _)| path.clone()).collect();
+        settled.into_iter().filter_map(|path| self.events.remove(&path).map(|(kind, _)| WatchEvent { kind, path })).collect()
+    }
+    /// Returns how long to wait until the next change settles.
+    fn next_deadline(&self, debounce: Duration) -> Option<Duration> {
+        self.events.values().map(|(_, seen)| debounce.saturating_sub(seen.elapsed())).min()
+    }
+}
+
+/// Translates a watcher error into an event reported to the caller.
+fn error_event(error: notify::Error, root: &Path) -> WatchEvent {
+    let path = error.paths.first().cloned().unwrap_or_else(|| root.to_path_buf());
+    WatchEvent { kind: WatchEventKind::Error { message: error.to_string() }, path }
+}
+
+/// Translates a raw event into changes of single paths.
+fn translate(event: notify::Event) -> Vec<(PathBuf, WatchEventKind)> {
+    let mut paths = event.paths.into_iter();
+    match event.kind {
+        EventKind::Create(_) => paths.map(|path| (path, WatchEventKind::Created)).collect(),
+        EventKind::Remove(_) => paths.map(|path| (path, WatchEventKind::Removed)).collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => match (paths.next(), paths.next()) {
+            (Some(from), Some(to)) => vec![(to, WatchEventKind::Renamed { from })],
+            _ => Vec::new(),
+        },
+        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths.map(|path| (path, WatchEventKind::Removed)).collect(),
+        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => paths.map(|path| (path, WatchEventKind::Created)).collect(),
+        // Renames of unknown direction, check what is there now
+        EventKind::Modify(ModifyKind::Name(_)) => paths.map(|path| {
+            let kind = if path.exists() { WatchEventKind::Created } else { WatchEventKind::Removed };
+            (path, kind)
+        }).collect(),
+        EventKind::Modify(_) => paths.map(|path| (path, WatchEventKind::Modified)).collect(),
+        EventKind::Access(_) | EventKind::Any | EventKind::Other => Vec::new(),
+    }
+}
+
+// #===============#
+// #=== WATCHER ===#
+
+/// Watches a directory tree and reports debounced changes, either as a blocking iterator or through a callback.
+///
+/// Uses the native watcher of the platform (inotify on Linux) and falls back to polling if it is unavailable,
+/// e.g. when the inotify watch limit is reached. Watching stops when the watcher is dropped.
+pub struct Watcher {
+    receiver: Option<mpsc::Receiver<WatchEvent>>,
+    _watcher: Box<dyn notify::Watcher + Send>,
+}
+impl Watcher {
+    /// Starts watching the directory. Changes are received with [`Watcher::recv`] or by iterating over the watcher.
+    pub fn new(dir: impl AsRef<Path>, options: WatchOptions) -> Result<Self, Error> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = Self::start(dir.as_ref(), options, move |event| { let _ = sender.send(event); })?;
+        watcher.receiver = Some(receiver);
+        Ok(watcher)
+    }
+    /// Starts watching the directory, calling the callback with every change on a background thread.
+    pub fn with_callback(dir: impl AsRef<Path>, options: WatchOptions, callback: impl FnMut(WatchEvent) + Send + 'static) -> Result<Self, Error> {
+        Self::start(dir.as_ref(), options, callback)
+    }
+    /// Blocks until the next change. Returns `None` if this watcher uses a callback. Errors are received as [`WatchEventKind::Error`].
+    pub fn recv(&self) -> Option<WatchEvent> {
+        self.receiver.as_ref()?.recv().ok()
+    }
+    /// Blocks until the next change or the timeout. Returns `None` on timeout.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<WatchEvent> {
+        self.receiver.as_ref()?.recv_timeout(timeout).ok()
+    }
+    /// Returns a blocking iterator over all changes.
+    pub fn iter(&self) -> impl Iterator<Item = WatchEvent> + '_ {
+        std::iter::from_fn(|| self.recv())
+    }
+    /// Starts the watcher and the thread debouncing its events into the callback.
+    fn start(dir: &Path, options: WatchOptions, mut callback: impl FnMut(WatchEvent) + Send + 'static) -> Result<Self, Error> {
+        let root = std::fs::canonicalize(dir)?;
+        let mode = if options.recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        let (sender, raw) = mpsc::channel::<notify::Result<notify::Event>>();
+
+        // Prefer the native watcher, fall back to polling
+        let native = match options.force_polling {
+            false => notify::recommended_watcher(sender.clone()).and_then(|mut watcher| watcher.watch(&root, mode).map(|_| watcher)).ok(),
+            true => None,
+        };
+        let watcher: Box<dyn notify::Watcher + Send> = match native {
+            Some(watcher) => Box::new(watcher),
+            None => {
+                let config = notify::Config::default().with_poll_interval(options.poll_interval);
+                let mut watcher = notify::PollWatcher::new(sender, config)?;
+                watcher.watch(&root, mode)?;
+                Box::new(watcher)
+            },
+        };
+
+        // Debounce raw events until the watcher is dropped and the channel closes
+        thread::spawn(move || {
+            let mut pending = Pending::default();
+            loop {
+                let received = match pending.next_deadline(options.debounce) {
+                    Some(deadline) => raw.recv_timeout(deadline),
+                    None => raw.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+                };
+                match received {
+                    Ok(Ok(event)) => for (path, kind) in translate(event) {
+                        let relative = path.strip_prefix(&root).unwrap_or(&path);
+                        if relative.as_os_str().is_empty() || !options.matches(relative) {
+                            continue;
+                        }
+                        pending.push(path, kind);
+                    },
+                    Ok(Err(error)) => callback(error_event(error, &root)),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {},
+                    // Deliver the changes still waiting for their debounce before stopping
+                    Err(mpsc::RecvTimeoutError::Disconnected) => {
+                        pending.drain().into_iter().for_each(&mut callback);
+                        break;
+                    },
+                }
+                for event in pending.settled(options.debounce) {
+                    callback(event);
+                }
+            }
+        });
+
+        Ok(Watcher { receiver: None, _watcher: watcher })
+    }
+}
@@ -0,0 +1,356 @@
+use std::io;
+use serde::Serialize;
+
+use crate::Error;
+
+#[test]
+fn test() {
+    use crate::{Format, Json, Toml};
+
+    let value = serde_json::json!({ "name": "x", "list": [1, [2, 3], { "a": "b,c" }], "empty": [] });
+
+    let options = WriteOptions::new().indent(4).arrays(ArrayLayout::Inline);
+    let json = String::from_utf8(Json::to_bytes_with(&value, &options).unwrap()).unwrap();
+    assert_eq!(json, "{\n    \"empty\": [],\n    \"list\": [1, [2, 3], {\"a\": \"b,c\"}],\n    \"name\": \"x\"\n}\n");
+
+    let options = WriteOptions::new().line_ending(LineEnding::Crlf);
+    let json = String::from_utf8(Json::to_bytes_with(&value, &options).unwrap()).unwrap();
+    assert!(json.starts_with("{\r\n  \"empty\": [],\r\n  \"list\": [\r\n    1,\r\n    [\r\n      2,") && json.ends_with("}\r\n"));
+
+    let options = WriteOptions::new().compact().trailing_newline(false);
+    assert_eq!(Json::to_bytes_with(&value, &options).unwrap(), serde_json::to_vec(&value).unwrap());
+
+    let value = toml::toml! { list = [1, [2, 3], "a, \"]b"] text = "a\n  b" [server] tags = [] };
+    let toml = String::from_utf8(Toml::to_bytes_with(&value, &WriteOptions::new()).unwrap()).unwrap();
+    assert_eq!(toml, "list = [\n  1,\n  [2, 3],\n  'a, \"]b',\n]\ntext = \"\"\"\na\n  b\"\"\"\n\n[server]\ntags = []\n");
+    assert_eq!(Toml::from_bytes::<toml::Table>(toml.as_bytes()).unwrap(), value);
+
+    // Multiline strings inside arrays keep their content
+    let value: toml::Table = "list = [\"a\\n]b = [1]\", 'c']\n[server]\nnested = [[\"x\\ny\"]]\n".parse().unwrap();
+    for options in [WriteOptions::new(), WriteOptions::new().line_ending(LineEnding::Crlf), WriteOptions::new().arrays(ArrayLayout::Inline).line_ending(LineEnding::Crlf)] {
+        let toml = Toml::to_bytes_with(&value, &options).unwrap();
+        assert_eq!(Toml::from_bytes::<toml::Table>(&toml).unwrap(), value);
+    }
+
+    // Line breaks inside strings survive CRLF line endings
+    let value = serde_json::json!({ "text": "a\nb\r\nc", "list": ["d\ne"] });
+    let options = WriteOptions::new().line_ending(LineEnding::Crlf);
+    let toml = Toml::to_bytes_with(&value, &options).unwrap();
+    assert!(!toml.windows(2).any(|pair| pair[0] != b'\r' && pair[1] == b'\n') && !toml.starts_with(b"\n"));
+    assert_eq!(Toml::from_bytes::<serde_json::Value>(&toml).unwrap(), value);
+    #[cfg(feature = "yaml")]
+    {
+        let yaml = crate::Yaml::to_bytes_with(&value, &options).unwrap();
+        assert_eq!(crate::Yaml::from_bytes::<serde_json::Value>(&yaml).unwrap(), value);
+        assert!(yaml.ends_with(b"\r\n") && !yaml.windows(2).any(|pair| pair[0] != b'\r' && pair[1] == b'\n'));
+        let yaml = crate::Yaml::to_bytes_with(&value, &WriteOptions::new().trailing_newline(false)).unwrap();
+        assert!(!yaml.ends_with(b"\n"));
+    }
+}
+
+// #=====================#
+// #=== WRITE OPTIONS ===#
+
+/// How arrays are laid out in pretty printed files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArrayLayout {
+    /// Every element on its own line
+    #[default]
+    Multiline,
+    /// All elements on the same line as the array
+    Inline,
+}
+
+/// Line endings used in written files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    /// Unix line endings (`\n`)
+    #[default]
+    Lf,
+    /// Windows line endings (`\r\n`)
+    Crlf,
+}
+
+/// Options controlling how text formats are written, accepted by all `*_with` writers and [`DirStore::open_with`](crate::DirStore::open_with).
+///
+/// Edits of single keys like [`Toml::set_key`](crate::Toml::set_key) keep the existing formatting of the file instead.
+/// Binary formats ignore them, and binary-only writers like [`Bincode::create_with_header`](crate::Bincode::create_with_header) do not take them. Formats without the matching serializer settings ignore the options
+/// they cannot apply, e.g. YAML always uses its own indentation and array layout.
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    pretty: bool,
+    indent: usize,
+    arrays: ArrayLayout,
+    trailing_newline: bool,
+    line_ending: LineEnding,
+}
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions { pretty: true, indent: 2, arrays: ArrayLayout::Multiline, trailing_newline: true, line_ending: LineEnding::Lf }
+    }
+}
+impl WriteOptions {
+    /// Creates the default options, pretty printed with 2 spaces, multiline arrays, a trailing newline and LF line endings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Writes everything as compact as the format allows.
+    pub fn compact(self) -> Self {
+        self.pretty(false)
+    }
+    /// Sets if the content is pretty printed over multiple lines.
+    pub fn pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+    /// Sets the number of spaces per indentation level.
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+    /// Sets how arrays are laid out when pretty printing.
+    pub fn arrays(mut self, arrays: ArrayLayout) -> Self {
+        self.arrays = arrays;
+        self
+    }
+    /// Sets if the file ends with a single newline.
+    pub fn trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+    /// Sets the line endings of the file.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+    /// Returns true if the content should be pretty printed.
+    pub(crate) fn is_pretty(&self) -> bool {
+        self.pretty
+    }
+    /// Returns true if arrays should be pretty printed with one element per line.
+    pub(crate) fn multiline_arrays(&self) -> bool {
+        self.pretty && self.arrays == ArrayLayout::Multiline
+    }
+    /// Returns a single indentation level.
+    pub(crate) fn indentation(&self) -> String {
+        " ".repeat(self.indent)
+    }
+    /// Applies the trailing newline and line ending settings to the serialized text.
+    /// Every `\n` is converted, so serializers must not leave raw line breaks inside string values when CRLF is requested.
+    pub(crate) fn finish(&self, text: String) -> Vec<u8> {
+        let mut text = match self.trailing_newline {
+            true => format!("{}\n", text.trim_end_matches('\n')),
+            false => text.trim_end_matches('\n').to_string(),
+        };
+        if self.line_ending == LineEnding::Crlf {
+            text = text.replace('\n', "\r\n");
+        }
+        text.into_bytes()
+    }
+}
+
+// #============#
+// #=== JSON ===#
+
+/// JSON formatter supporting a custom indentation and inline arrays.
+struct JsonFormatter {
+    indent: Vec<u8>,
+    inline_arrays: bool,
+    level: usize,
+    has_value: bool,
+    /// Number of open inline arrays, everything inside them is written on one line
+    inline: usize,
+}
+impl JsonFormatter {
+    /// Writes a newline followed by the indentation of the current level.
+    fn newline<W: ?Sized + io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b"\n")?;
+        for _ in 0..self.level {
+            writer.write_all(&self.indent)?;
+        }
+        Ok(())
+    }
+    /// Opens an array or object.
+    fn begin<W: ?Sized + io::Write>(&mut self, writer: &mut W, bracket: &[u8]) -> io::Result<()> {
+        if self.inline == 0 {
+            self.level += 1;
+            self.has_value = false;
+        }
+        writer.write_all(bracket)
+    }
+    /// Closes an array or object.
+    fn end<W: ?Sized + io::Write>(&mut self, writer: &mut W, bracket: &[u8]) -> io::Result<()> {
+        if self.inline == 0 {
+            self.level -= 1;
+            if self.has_value {
+                self.newline(writer)?;
+            }
+        }
+        writer.write_all(bracket)
+    }
+    /// Separates an element from the previous one.
+    fn separate<W: ?Sized + io::Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        if self.inline > 0 {
+            return writer.write_all(if first { b"" } else { b", " });
+        }
+        if !first {
+            writer.write_all(b",")?;
+        }
+        self.newline(writer)
+    }
+}
+impl serde_json::ser::Formatter for JsonFormatter {
+    fn begin_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        if self.inline_arrays {
+            self.inline += 1;
+            return writer.write_all(b"[");
+        }
+        self.begin(writer, b"[")
+    }
+    fn end_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        if self.inline_arrays {
+            self.inline -= 1;
+            return writer.write_all(b"]");
+        }
+        self.end(writer, b"]")
+    }
+    fn begin_array_value<W: ?Sized + io::Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        self.separate(writer, first)
+    }
+    fn end_array_value<W: ?Sized + io::Write>(&mut self, _writer: &mut W) -> io::Result<()> {
+        self.has_value = true;
+        Ok(())
+    }
+    fn begin_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.begin(writer, b"{")
+    }
+    fn end_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.end(writer, b"}")
+    }
+    fn begin_object_key<W: ?Sized + io::Write>(&mut self, writer: &mut W, first: bool) -> io::Result<()> {
+        self.separate(writer, first)
+    }
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+    fn end_object_value<W: ?Sized + io::Write>(&mut self, _writer: &mut W) -> io::Result<()> {
+        self.has_value = true;
+        Ok(())
+    }
+}
+
+/// Serializes the struct into JSON using the options.
+pub(crate) fn to_json<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<String, Error> {
+    if !options.is_pretty() {
+        return Ok(serde_json::to_string(content)?);
+    }
+    let formatter = JsonFormatter {
+        indent: options.indentation().into_bytes(),
+        inline_arrays: !options.multiline_arrays(),
+        level: 0,
+        has_value: false,
+        inline: 0,
+    };
+    let mut buffer = Vec::new();
+    content.serialize(&mut serde_json::Serializer::with_formatter(&mut buffer, formatter))?;
+    Ok(String::from_utf8(buffer).expect("serde_json always writes valid UTF-8"))
+}
+
+// #============#
+// #=== TOML ===#
+
+/// Escapes the string into a single line TOML basic string.
+fn toml_escaped(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for char in text.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char if char.is_control() => escaped.push_str(&format!("\\u{:04X}", char as u32)),
+            char => escaped.push(char),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Rewrites strings containing line breaks into single line strings, so converting line endings never touches their content.
+fn toml_single_line_strings(value: &mut toml_edit::Value) {
+    match value {
+        toml_edit::Value::String(string) if string.value().contains(['\n', '\r']) => {
+            let decor = string.decor().clone();
+            *value = toml_escaped(string.value()).parse().expect("escaped strings are valid TOML values");
+            *value.decor_mut() = decor;
+        },
+        toml_edit::Value::Array(array) => array.iter_mut().for_each(toml_single_line_strings),
+        toml_edit::Value::InlineTable(table) => table.iter_mut().for_each(|(_, value)| toml_single_line_strings(value)),
+        _ => {},
+    }
+}
+
+/// Lays out the array with one element per line. Nested values stay inline.
+fn toml_multiline_array(array: &mut toml_edit::Array, indent: &str) {
+    if array.is_empty() {
+        return;
+    }
+    for element in array.iter_mut() {
+        element.decor_mut().set_prefix(format!("\n{indent}"));
+        element.decor_mut().set_suffix("");
+    }
+    array.set_trailing_comma(true);
+    array.set_trailing("\n");
+}
+
+/// Applies the options to every value of the table and its subtables.
+fn toml_layout(table: &mut toml_edit::Table, options: &WriteOptions, indent: &str) {
+    for (_, item) in table.iter_mut() {
+        match item {
+            toml_edit::Item::Value(value) => {
+                if options.line_ending == LineEnding::Crlf {
+                    toml_single_line_strings(value);
+                }
+                if let (true, toml_edit::Value::Array(array)) = (options.multiline_arrays(), value) {
+                    toml_multiline_array(array, indent);
+                }
+            },
+            toml_edit::Item::Table(table) => toml_layout(table, options, indent),
+            toml_edit::Item::ArrayOfTables(tables) => tables.iter_mut().for_each(|table| toml_layout(table, options, indent)),
+            toml_edit::Item::None => {},
+        }
+    }
+}
+
+/// Serializes the struct into TOML using the options.
+/// TOML is always written in its regular style, pretty printing only affects the array layout.
+/// With CRLF line endings, strings containing line breaks are written escaped on a single line.
+pub(crate) fn to_toml<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<String, Error> {
+    let text = toml::to_string(content)?;
+    if !options.multiline_arrays() && options.line_ending == LineEnding::Lf {
+        return Ok(text);
+    }
+
+    // Lay out the document tree instead of the text, so string content is never touched
+    let mut document = text.parse::<toml_edit::DocumentMut>()?;
+    toml_layout(document.as_table_mut(), options, &options.indentation());
+    Ok(document.to_string())
+}
+
+// #===========#
+// #=== RON ===#
+
+/// Serializes the struct into RON using the options.
+#[cfg(feature = "ron")]
+pub(crate) fn to_ron<T: Serialize + ?Sized>(content: &T, options: &WriteOptions) -> Result<String, Error> {
+    if !options.is_pretty() {
+        return Ok(ron::to_string(content)?);
+    }
+    let config = ron::ser::PrettyConfig::new()
+        .indentor(options.indentation())
+        .new_line("\n")
+        .compact_arrays(!options.multiline_arrays());
+    Ok(ron::ser::to_string_pretty(content, config)?)
+}